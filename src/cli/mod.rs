@@ -1,4 +1,8 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use clap_complete::Shell;
+use crate::models::PriceOverride;
 
 #[derive(Parser)]
 #[command(name = "cc-monitor")]
@@ -7,17 +11,444 @@ use clap::{Parser, Subcommand};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
+    
+    /// When to use colored output (`NO_COLOR` is honored in auto mode)
+    #[arg(long, global = true, value_enum, default_value_t)]
+    pub color: ColorMode,
+    
+    /// Draw bars, arrows and borders with plain ASCII (also set by `CC_MONITOR_ASCII`)
+    #[arg(long, global = true)]
+    pub ascii: bool,
+    
+    /// Currency code for displayed costs (e.g. EUR, GBP)
+    #[arg(long, global = true, default_value = "USD")]
+    pub currency: String,
+    
+    /// Exchange rate from USD, overriding the bundled approximate rate
+    #[arg(long, global = true)]
+    pub rate: Option<f64>,
+    
+    /// Digit grouping for large numbers; detected from the locale when omitted
+    #[arg(long, global = true, value_enum)]
+    pub thousands_sep: Option<ThousandsSeparator>,
+    
+    /// Footer under text reports: generation time and the data's date range
+    #[arg(long, global = true, value_enum, default_value_t)]
+    pub footer_style: FooterStyle,
+    
+    /// Print `--json` output on a single line instead of pretty-printed
+    #[arg(long, global = true)]
+    pub compact: bool,
+    
+    /// Leave cache-read tokens out of token totals (costs are unchanged)
+    #[arg(long, global = true)]
+    pub exclude_cache_read: bool,
+    
+    /// Count synthetic entries (API errors, interrupted turns) in the totals
+    #[arg(long, global = true)]
+    pub include_synthetic: bool,
+    
+    /// Keep entries with zero token usage (they open day and session buckets)
+    #[arg(long, global = true)]
+    pub include_empty: bool,
+    
+    /// Count raw transcript usage, without de-duplicating cache tokens across resumed sessions
+    #[arg(long, global = true, visible_alias = "raw")]
+    pub no_dedupe_cache: bool,
+    
+    /// Report a resumed session and its continuations (same project, each starting
+    /// within 10 minutes of the previous ending) as one session
+    #[arg(long, global = true)]
+    pub merge_sessions: bool,
+    
+    /// Override a model's rates in USD per million tokens, e.g. `claude-opus-4=15,75`;
+    /// MODEL may be an id prefix. Repeatable.
+    #[arg(long, value_name = "MODEL=IN,OUT[,CACHE_WRITE,CACHE_READ]", global = true)]
+    pub price: Vec<PriceOverride>,
+    
+    /// Replace project paths, session ids and data directories with pseudonyms
+    /// such as `project-1f0c9e3a`, for sharing reports; they change on every run
+    #[arg(long, global = true)]
+    pub anonymize: bool,
+    
+    /// Keep entries timestamped more than a day in the future (normally skipped as clock skew)
+    #[arg(long, global = true)]
+    pub allow_future: bool,
+    
+    /// Print load timings (globbing, parsing, aggregation) and file/entry counts to stderr
+    #[arg(long, global = true)]
+    pub profile: bool,
+    
+    /// Round costs in JSON output to PLACES decimals (unrounded by default)
+    #[arg(long, value_name = "PLACES", global = true, value_parser = clap::value_parser!(u32).range(0..=10))]
+    pub round: Option<u32>,
+    
+    /// Write report output to FILE instead of stdout, creating parent directories
+    #[arg(long = "output", short = 'o', value_name = "FILE", global = true)]
+    pub output_file: Option<PathBuf>,
+    
+    /// Log more to stderr: -v info, -vv debug, -vvv trace (`RUST_LOG` wins when set)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    pub verbose: u8,
+    
+    /// Log errors only
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+}
+
+impl Cli {
+    /// `--ascii`, or a non-empty `CC_MONITOR_ASCII` other than "0"
+    pub fn ascii_enabled(&self) -> bool {
+        self.ascii || std::env::var("CC_MONITOR_ASCII").is_ok_and(|v| !v.is_empty() && v != "0")
+    }
+    
+    /// Log filter implied by `-v`/`-q`; warnings by default
+    pub fn log_level(&self) -> &'static str {
+        if self.quiet {
+            return "error";
+        }
+        match self.verbose {
+            0 => "warn",
+            1 => "info",
+            2 => "debug",
+            _ => "trace",
+        }
+    }
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// Launch interactive dashboard
-    Dashboard,
+    Dashboard(DashboardArgs),
     
     /// Show compact statusline (for use with Claude hooks)
     Statusline {
-        /// Read JSON input from stdin (for hook integration)
+        /// Read JSON input from stdin (for hook integration); pass `--stdin=false` to skip
+        #[arg(long, default_value_t = true, num_args = 0..=1, default_missing_value = "true", action = clap::ArgAction::Set)]
+        stdin: bool,
+        
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+        
+        /// Keep running and reprint every SECS seconds (or on SIGUSR1)
+        #[arg(long, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+        watch: Option<u64>,
+        
+        /// Hours of recent usage averaged into the burn rate
+        #[arg(long, value_name = "HOURS", default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+        burn_window: u32,
+        
+        /// Average the burn rate over completed hours only, leaving out the current one
+        #[arg(long)]
+        completed_hours: bool,
+        
+        /// Show an estimated share of this plan's 5-hour block quota used so far
+        #[arg(long, value_enum)]
+        plan: Option<Plan>,
+        
+        /// Print the JSON Schema of the `--json` output and exit
+        #[arg(long)]
+        explain: bool,
+    },
+    
+    /// Show today, 7-day, this-month and all-time totals
+    Summary {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+        
+        /// Refresh every SECS seconds until Ctrl-C (one JSON object per line with --json)
+        #[arg(long, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+        watch: Option<u64>,
+        
+        /// Read transcript JSONL from stdin instead of the Claude data directories
+        #[arg(long, conflicts_with = "watch")]
+        stdin: bool,
+        
+        /// Exit with status 2 after printing if this month's cost exceeds USD
+        #[arg(long, value_name = "USD", conflicts_with = "watch")]
+        fail_over: Option<f64>,
+        
+        /// Also break all-time usage down by CLAUDE_CONFIG_DIR data directory
+        #[arg(long)]
+        by_source: bool,
+        
+        /// Render the usage stats through a `{{...}}` template FILE instead of the built-in output
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["json", "watch"])]
+        template: Option<PathBuf>,
+    },
+    
+    /// Show usage by hour of day across all history
+    Hourly {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+        
+        /// Read transcript JSONL from stdin instead of the Claude data directories
+        #[arg(long)]
+        stdin: bool,
+    },
+    
+    /// Show usage by day of week, with a weekday vs weekend comparison
+    Weekday {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+        
+        /// Read transcript JSONL from stdin instead of the Claude data directories
+        #[arg(long)]
+        stdin: bool,
+    },
+    
+    /// Print usage as Prometheus metrics, e.g. for node_exporter's textfile collector
+    Metrics {
+        /// Read transcript JSONL from stdin instead of the Claude data directories
+        #[arg(long)]
+        stdin: bool,
+    },
+    
+    /// Print tidy per-day, per-model rows for charting tools
+    Timeseries {
+        /// Output format
+        #[arg(long, value_enum, default_value_t)]
+        format: TimeSeriesFormat,
+        
+        /// Read transcript JSONL from stdin instead of the Claude data directories
+        #[arg(long)]
+        stdin: bool,
+    },
+    
+    /// Show what your usage would have cost with every request on one model
+    Simulate {
+        /// Model id whose rates to apply, e.g. claude-3-5-haiku-20241022
+        model: String,
+        
+        /// Only simulate the current calendar month
+        #[arg(long)]
+        this_month: bool,
+        
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+        
+        /// Read transcript JSONL from stdin instead of the Claude data directories
+        #[arg(long)]
+        stdin: bool,
+    },
+    
+    /// Check logged costUSD values against costs recomputed from the pricing table
+    Verify {
+        /// Largest allowed difference per model, as a fraction of its logged cost
+        #[arg(long, default_value_t = 0.05, value_name = "FRACTION")]
+        tolerance: f64,
+        
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+        
+        /// Read transcript JSONL from stdin instead of the Claude data directories
+        #[arg(long)]
+        stdin: bool,
+    },
+    
+    /// List the directories searched for transcripts and what was found in each
+    Paths {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Write all aggregated stats to a JSON file
+    Export {
+        /// Destination file, or `-` for stdout
+        output: PathBuf,
+        
+        /// Pretty-print the JSON
+        #[arg(long)]
+        pretty: bool,
+        
+        /// Write one compact JSON object per daily/session/monthly row instead
+        #[arg(long, conflicts_with = "pretty")]
+        ndjson: bool,
+        
+        /// Read transcript JSONL from stdin instead of the Claude data directories
         #[arg(long)]
         stdin: bool,
     },
-}
\ No newline at end of file
+    
+    /// List every model in your history with lifetime totals
+    Models {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+        
+        /// Group dated model ids into families such as "Sonnet 3.5"
+        #[arg(long)]
+        by_family: bool,
+    },
+    
+    /// Compare the current week or month with the one before it
+    Compare {
+        /// Period to compare against its predecessor
+        #[arg(long, value_enum, default_value_t)]
+        period: ComparePeriod,
+        
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Print a shell completion script to stdout
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+}
+
+#[derive(Args, Default)]
+pub struct DashboardArgs {
+    /// Metric emphasized by the overview chart
+    #[arg(long, value_enum, default_value_t)]
+    pub unit: Unit,
+    
+    /// Row order for the daily and sessions lists
+    #[arg(long, value_enum, default_value_t)]
+    pub order: Order,
+    
+    /// Show only each project's directory name in the sessions list (toggle with `b`)
+    #[arg(long)]
+    pub basename: bool,
+    
+    /// Show session project paths relative to this directory; paths outside it stay in full
+    #[arg(long, value_name = "PATH")]
+    pub projects_root: Option<PathBuf>,
+    
+    /// Start with session times shown relative to now (toggle with `r`)
+    #[arg(long)]
+    pub relative: bool,
+    
+    /// Group the Models tab by model family instead of exact id
+    #[arg(long)]
+    pub by_family: bool,
+    
+    /// Add a sparkline of each month's daily costs to the Monthly tab
+    #[arg(long)]
+    pub spark: bool,
+    
+    /// Chart the five costliest projects on the Overview tab
+    #[arg(long)]
+    pub top_projects: bool,
+    
+    /// Start with the Daily tab split into input, output and cache columns (toggle with `d`)
+    #[arg(long)]
+    pub detailed: bool,
+    
+    /// Quit on the first `q` press instead of asking for a second one
+    #[arg(long)]
+    pub no_confirm_quit: bool,
+    
+    /// Daily cost in USD above which Daily and Monthly list costs turn yellow [default: 5]
+    #[arg(long, value_name = "USD")]
+    pub cost_warn: Option<f64>,
+    
+    /// Daily cost in USD above which they turn red [default: 20]
+    #[arg(long, value_name = "USD")]
+    pub cost_high: Option<f64>,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum Unit {
+    #[default]
+    Cost,
+    Tokens,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum Order {
+    DateAsc,
+    #[default]
+    DateDesc,
+    CostDesc,
+    TokensDesc,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum ComparePeriod {
+    /// Last 7 days against the 7 days before
+    Week,
+    /// This month so far against last month
+    #[default]
+    Month,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum TimeSeriesFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum Plan {
+    /// Claude Pro
+    Pro,
+    /// Claude Max with 5x Pro usage
+    Max5,
+    /// Claude Max with 20x Pro usage
+    Max20,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum FooterStyle {
+    /// No footer
+    None,
+    /// Generation time and the data's date range on one line
+    #[default]
+    Compact,
+    /// Also the timezone, cc-monitor version, day and session counts
+    Full,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum ThousandsSeparator {
+    /// 1,234,567
+    Comma,
+    /// 1.234.567
+    Period,
+    /// 1 234 567
+    Space,
+}
+
+impl ThousandsSeparator {
+    pub fn as_char(self) -> char {
+        match self {
+            ThousandsSeparator::Comma => ',',
+            ThousandsSeparator::Period => '.',
+            ThousandsSeparator::Space => ' ',
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum ColorMode {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    /// Resolve whether styling should be emitted on stdout
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => {
+                let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+                !no_color && std::io::stdout().is_terminal()
+            }
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use crate::cli::ComparePeriod;
+use crate::currency::Currency;
+use crate::data_loader::{DataLoader, LoadOptions};
+use crate::models::UsageStats;
+use crate::util::{format_number, glyph, outln, print_report_footer, print_token_definition_note, to_json_string, token_definition, token_total};
+use anyhow::Result;
+use chrono::{Datelike, Duration, Local, NaiveDate};
+
+struct PeriodTotals {
+    label: String,
+    tokens: u64,
+    cost: f64,
+}
+
+pub fn show_compare(period: ComparePeriod, json: bool, currency: &Currency, options: &LoadOptions) -> Result<()> {
+    let loader = DataLoader::new(options)?;
+    let stats = loader.load_all_usage()?;
+
+    if stats.is_empty() {
+        eprintln!("{}", loader.empty_state_message());
+        if !json {
+            return Ok(());
+        }
+    }
+
+    let today = Local::now().date_naive();
+    let (current, previous) = match period {
+        ComparePeriod::Week => week_totals(&stats, today),
+        ComparePeriod::Month => month_totals(&stats, today),
+    };
+
+    if json {
+        outln!("{}", to_json_string(&compare_json(&current, previous.as_ref()))?)?;
+        return Ok(());
+    }
+
+    outln!(
+        "{:<24} {:>15} tokens  {:>11}",
+        current.label,
+        format_number(current.tokens),
+        currency.format(current.cost, 2)
+    )?;
+    match previous {
+        Some(previous) => {
+            outln!(
+                "{:<24} {:>15} tokens  {:>11}",
+                previous.label,
+                format_number(previous.tokens),
+                currency.format(previous.cost, 2)
+            )?;
+            let token_delta = current.tokens as i64 - previous.tokens as i64;
+            let cost_delta = current.cost - previous.cost;
+            let token_sign = if token_delta < 0 { "-" } else { "+" };
+            let cost_sign = if cost_delta < 0.0 { "-" } else { "+" };
+            outln!(
+                "{:<24} {} {}{} tokens ({})  {} {}{} ({})",
+                "Change",
+                indicator(token_delta as f64),
+                token_sign,
+                format_number(token_delta.unsigned_abs()),
+                format_pct(pct_change(current.tokens as f64, previous.tokens as f64)),
+                indicator(cost_delta),
+                cost_sign,
+                currency.format(cost_delta.abs(), 2),
+                format_pct(pct_change(current.cost, previous.cost))
+            )?;
+        }
+        None => outln!("{:<24} no prior data.", "Previous")?,
+    }
+    print_token_definition_note()?;
+    print_report_footer(&stats)?;
+
+    Ok(())
+}
+
+/// Last 7 days, including today, against the 7 days before them
+fn week_totals(stats: &UsageStats, today: NaiveDate) -> (PeriodTotals, Option<PeriodTotals>) {
+    let range_totals = |start: NaiveDate, end: NaiveDate| {
+        let days: Vec<_> = stats.daily.iter()
+            .filter(|d| d.date >= start && d.date <= end)
+            .collect();
+        let totals = PeriodTotals {
+            label: format!("{} to {}", start, end),
+            tokens: days.iter().map(|d| token_total(&d.tokens)).sum(),
+            cost: days.iter().map(|d| d.total_cost).sum(),
+        };
+        (totals, !days.is_empty())
+    };
+
+    let (current, _) = range_totals(today - Duration::days(6), today);
+    let (previous, has_data) = range_totals(today - Duration::days(13), today - Duration::days(7));
+    (current, has_data.then_some(previous))
+}
+
+/// This calendar month so far against the whole of last month
+fn month_totals(stats: &UsageStats, today: NaiveDate) -> (PeriodTotals, Option<PeriodTotals>) {
+    let month_totals = |month: String| {
+        let found = stats.monthly.iter().find(|m| m.month == month);
+        let totals = PeriodTotals {
+            tokens: found.map(|m| token_total(&m.tokens)).unwrap_or_default(),
+            cost: found.map(|m| m.total_cost).unwrap_or_default(),
+            label: month,
+        };
+        (totals, found.is_some())
+    };
+
+    let previous_month = today.with_day(1)
+        .and_then(|first| first.pred_opt())
+        .unwrap_or(today);
+    let (current, _) = month_totals(format!("{:04}-{:02}", today.year(), today.month()));
+    let (previous, has_data) = month_totals(format!(
+        "{:04}-{:02}",
+        previous_month.year(),
+        previous_month.month()
+    ));
+    (current, has_data.then_some(previous))
+}
+
+/// Percentage change from `previous`, or `None` when there's nothing to compare against
+fn pct_change(current: f64, previous: f64) -> Option<f64> {
+    if previous == 0.0 {
+        None
+    } else {
+        Some((current - previous) / previous * 100.0)
+    }
+}
+
+fn format_pct(pct: Option<f64>) -> String {
+    match pct {
+        Some(pct) => format!("{:+.1}%", pct),
+        None => "n/a".to_string(),
+    }
+}
+
+fn indicator(delta: f64) -> &'static str {
+    if delta > 0.0 {
+        glyph("▲", "^")
+    } else if delta < 0.0 {
+        glyph("▼", "v")
+    } else {
+        "="
+    }
+}
+
+fn compare_json(current: &PeriodTotals, previous: Option<&PeriodTotals>) -> serde_json::Value {
+    let totals_json = |totals: &PeriodTotals| {
+        serde_json::json!({
+            "period": totals.label,
+            "tokens": totals.tokens,
+            "cost": totals.cost
+        })
+    };
+
+    serde_json::json!({
+        "current": totals_json(current),
+        "previous": previous.map(totals_json),
+        "delta": previous.map(|p| serde_json::json!({
+            "tokens": current.tokens as i64 - p.tokens as i64,
+            "cost": current.cost - p.cost
+        })),
+        "pct_change": previous.map(|p| serde_json::json!({
+            "tokens": pct_change(current.tokens as f64, p.tokens as f64),
+            "cost": pct_change(current.cost, p.cost)
+        })),
+        "token_definition": token_definition()
+    })
+}
@@ -0,0 +1,12 @@
+use crate::cli::Cli;
+use crate::util;
+use anyhow::Result;
+use clap::CommandFactory;
+use clap_complete::{generate, Shell};
+
+pub fn show_completions(shell: Shell) -> Result<()> {
+    let mut cmd = Cli::command();
+    let name = cmd.get_name().to_string();
+    generate(shell, &mut cmd, name, &mut util::output());
+    Ok(())
+}
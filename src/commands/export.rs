@@ -0,0 +1,71 @@
+use crate::data_loader::{DataLoader, LoadOptions};
+use crate::models::UsageStats;
+use crate::util;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+pub fn export_stats(
+    output: &Path,
+    pretty: bool,
+    ndjson: bool,
+    read_stdin: bool,
+    options: &LoadOptions,
+) -> Result<()> {
+    let loader = DataLoader::for_input(read_stdin, options)?;
+    let stats = loader.load_all_usage()?;
+    if stats.is_empty() {
+        eprintln!("{}", loader.empty_state_message());
+    }
+
+    // `-` streams to stdout (or the global `--output` file) so NDJSON can be
+    // piped straight into jq
+    let to_stdout = output == Path::new("-");
+    let mut writer: BufWriter<Box<dyn Write>> = if to_stdout {
+        BufWriter::new(util::output())
+    } else {
+        let file = File::create(output)
+            .with_context(|| format!("Failed to create {}", output.display()))?;
+        BufWriter::new(Box::new(file))
+    };
+
+    if ndjson {
+        write_ndjson(&mut writer, &stats)?;
+    } else {
+        writeln!(writer, "{}", util::to_json(&stats, pretty)?)?;
+    }
+    writer.flush()?;
+
+    if !to_stdout {
+        util::outln!("Exported usage stats to {}", output.display())?;
+    }
+
+    Ok(())
+}
+
+/// One compact object per daily, session and monthly row, tagged with a `type` field
+fn write_ndjson(writer: &mut impl Write, stats: &UsageStats) -> Result<()> {
+    for day in &stats.daily {
+        write_row(writer, "daily", day)?;
+    }
+    for session in &stats.sessions {
+        write_row(writer, "session", session)?;
+    }
+    for month in &stats.monthly {
+        write_row(writer, "monthly", month)?;
+    }
+    Ok(())
+}
+
+fn write_row(writer: &mut impl Write, kind: &str, row: &impl Serialize) -> Result<()> {
+    let mut value = serde_json::to_value(row)?;
+    if let Some(object) = value.as_object_mut() {
+        // Monthly rows already have their days emitted as separate lines
+        object.remove("daily_breakdown");
+        object.insert("type".to_string(), kind.into());
+    }
+    writeln!(writer, "{}", util::to_json(&value, false)?)?;
+    Ok(())
+}
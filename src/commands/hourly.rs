@@ -0,0 +1,65 @@
+use crate::currency::Currency;
+use crate::data_loader::{DataLoader, LoadOptions};
+use crate::models::TokenUsage;
+use crate::util::{format_number, glyph, outln, print_report_footer, print_token_definition_note, to_json_string, token_total};
+use anyhow::Result;
+
+/// Width of the longest bar in the text heatmap
+const BAR_WIDTH: usize = 40;
+
+pub fn show_hourly(json: bool, read_stdin: bool, currency: &Currency, options: &LoadOptions) -> Result<()> {
+    let loader = DataLoader::for_input(read_stdin, options)?;
+    let stats = loader.load_all_usage()?;
+
+    if stats.is_empty() {
+        eprintln!("{}", loader.empty_state_message());
+        if !json {
+            return Ok(());
+        }
+    }
+
+    // Bucket all history by local hour of day
+    let mut buckets: Vec<(TokenUsage, f64)> = vec![(TokenUsage::default(), 0.0); 24];
+    for local_hour in &stats.local_hours {
+        let hour = local_hour.hour as usize;
+        buckets[hour].0.add(&local_hour.tokens);
+        buckets[hour].1 += local_hour.total_cost;
+    }
+
+    if json {
+        let output: Vec<_> = buckets
+            .iter()
+            .enumerate()
+            .map(|(hour, (tokens, cost))| {
+                serde_json::json!({
+                    "hour": hour,
+                    "tokens": token_total(tokens),
+                    "cost": cost
+                })
+            })
+            .collect();
+        outln!("{}", to_json_string(&output)?)?;
+        return Ok(());
+    }
+
+    let max_cost = buckets.iter().map(|(_, cost)| *cost).fold(0.0, f64::max);
+    for (hour, (tokens, cost)) in buckets.iter().enumerate() {
+        let width = if max_cost > 0.0 {
+            ((cost / max_cost) * BAR_WIDTH as f64).round() as usize
+        } else {
+            0
+        };
+        outln!(
+            "{:02}:00 {:<bar_width$} {:>9} {:>14} tokens",
+            hour,
+            glyph("█", "#").repeat(width),
+            currency.format(*cost, 2),
+            format_number(token_total(tokens)),
+            bar_width = BAR_WIDTH
+        )?;
+    }
+    print_token_definition_note()?;
+    print_report_footer(&stats)?;
+
+    Ok(())
+}
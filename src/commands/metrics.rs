@@ -0,0 +1,86 @@
+use crate::data_loader::{DataLoader, LoadOptions};
+use crate::models::TokenUsage;
+use crate::util::output;
+use anyhow::Result;
+use std::fmt::Write as _;
+use std::io::Write;
+
+/// Print current usage in the Prometheus text exposition format, e.g. for
+/// node_exporter's textfile collector. Costs are always USD.
+pub fn show_metrics(read_stdin: bool, options: &LoadOptions) -> Result<()> {
+    let loader = DataLoader::for_input(read_stdin, options)?;
+    let stats = loader.load_all_usage()?;
+
+    if stats.is_empty() {
+        eprintln!("{}", loader.empty_state_message());
+    }
+
+    let today = stats.get_today_stats();
+    let mut out = String::new();
+
+    metric(&mut out, "cc_monitor_cost_today_usd", "gauge", "Cost of today's usage (local time) in USD");
+    sample(&mut out, "cc_monitor_cost_today_usd", &[], today.map(|d| d.total_cost).unwrap_or_default());
+
+    metric(&mut out, "cc_monitor_cost_month_usd", "gauge", "Cost of this calendar month's usage in USD");
+    sample(&mut out, "cc_monitor_cost_month_usd", &[], stats.get_month_stats().map(|m| m.total_cost).unwrap_or_default());
+
+    metric(&mut out, "cc_monitor_tokens_today", "gauge", "Tokens used today (local time) by token type");
+    token_samples(&mut out, "cc_monitor_tokens_today", &[], &today.map(|d| d.tokens.clone()).unwrap_or_default());
+
+    metric(&mut out, "cc_monitor_cost_usd_total", "counter", "All-time cost in USD by model");
+    for model in &stats.models {
+        sample(&mut out, "cc_monitor_cost_usd_total", &[("model", &model.model)], model.total_cost);
+    }
+
+    metric(&mut out, "cc_monitor_tokens_total", "counter", "All-time tokens by model and token type");
+    for model in &stats.models {
+        token_samples(&mut out, "cc_monitor_tokens_total", &[("model", &model.model)], &model.tokens);
+    }
+
+    metric(&mut out, "cc_monitor_sessions_total", "counter", "Sessions seen across all history");
+    sample(&mut out, "cc_monitor_sessions_total", &[], stats.sessions.len() as f64);
+
+    if let Some(last) = stats.sessions.iter().map(|s| s.last_activity).max() {
+        metric(&mut out, "cc_monitor_last_activity_timestamp_seconds", "gauge", "Unix time of the most recent usage entry");
+        sample(&mut out, "cc_monitor_last_activity_timestamp_seconds", &[], last.timestamp() as f64);
+    }
+
+    output().write_all(out.as_bytes())?;
+    Ok(())
+}
+
+fn metric(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: f64) {
+    let labels: Vec<_> = labels.iter()
+        .map(|(key, value)| format!("{}=\"{}\"", key, escape_label(value)))
+        .collect();
+    if labels.is_empty() {
+        let _ = writeln!(out, "{} {}", name, value);
+    } else {
+        let _ = writeln!(out, "{}{{{}}} {}", name, labels.join(","), value);
+    }
+}
+
+/// One sample per token type, with a `type` label added to `labels`
+fn token_samples(out: &mut String, name: &str, labels: &[(&str, &str)], tokens: &TokenUsage) {
+    let types = [
+        ("input", tokens.input_tokens),
+        ("output", tokens.output_tokens),
+        ("cache_creation", tokens.cache_creation_input_tokens),
+        ("cache_read", tokens.cache_read_input_tokens),
+    ];
+    for (kind, count) in types {
+        let mut labels = labels.to_vec();
+        labels.push(("type", kind));
+        sample(out, name, &labels, count as f64);
+    }
+}
+
+/// Label values escape backslashes, double quotes and newlines
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
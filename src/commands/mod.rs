@@ -1,3 +1,27 @@
+pub mod compare;
+pub mod completions;
+pub mod export;
+pub mod hourly;
+pub mod metrics;
+pub mod models;
+pub mod paths;
+pub mod simulate;
 pub mod statusline;
+pub mod summary;
+pub mod timeseries;
+pub mod verify;
+pub mod weekday;
 
-pub use statusline::*;
\ No newline at end of file
+pub use compare::*;
+pub use completions::*;
+pub use export::*;
+pub use hourly::*;
+pub use metrics::*;
+pub use models::*;
+pub use paths::*;
+pub use simulate::*;
+pub use statusline::*;
+pub use summary::*;
+pub use timeseries::*;
+pub use verify::*;
+pub use weekday::*;
\ No newline at end of file
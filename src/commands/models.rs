@@ -0,0 +1,53 @@
+use crate::currency::Currency;
+use crate::data_loader::{DataLoader, LoadOptions};
+use crate::models::ModelUsage;
+use crate::util::{format_number, outln, print_report_footer, print_token_definition_note, to_json_string, token_total};
+use anyhow::Result;
+use chrono::Local;
+
+pub fn show_models(json: bool, by_family: bool, currency: &Currency, options: &LoadOptions) -> Result<()> {
+    let loader = DataLoader::new(options)?;
+    let stats = loader.load_all_usage()?;
+    let families = if by_family { stats.models_by_family() } else { Vec::new() };
+
+    if json {
+        // Both are already sorted by cost, highest first
+        if by_family {
+            outln!("{}", to_json_string(&families)?)?;
+        } else {
+            outln!("{}", to_json_string(&stats.models)?)?;
+        }
+        return Ok(());
+    }
+
+    if stats.models.is_empty() {
+        eprintln!("{}", loader.empty_state_message());
+        return Ok(());
+    }
+
+    let rows: Vec<&ModelUsage> = if by_family {
+        families.iter().map(|f| &f.usage).collect()
+    } else {
+        stats.models.iter().collect()
+    };
+    let name_width = rows.iter().map(|m| m.model.len()).max().unwrap_or(0).max(5);
+    outln!(
+        "{:<name_width$}  {:>15}  {:>11}  {:>8}  {:<10}  Last seen",
+        if by_family { "Family" } else { "Model" }, "Tokens", "Cost", "Sessions", "First seen"
+    )?;
+    for model in rows {
+        outln!(
+            "{:<name_width$}  {:>15}  {:>11}  {:>8}  {:<10}  {}",
+            model.model,
+            format_number(token_total(&model.tokens)),
+            currency.format(model.total_cost, 2),
+            model.session_count,
+            model.first_seen.with_timezone(&Local).date_naive(),
+            model.last_seen.with_timezone(&Local).date_naive(),
+        )?;
+    }
+    print_token_definition_note()?;
+    print_report_footer(&stats)?;
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use crate::data_loader::{DataLoader, PathOrigin, PathProbe};
+use crate::util::{format_number, outln, pseudonym, to_json_string};
+use anyhow::Result;
+use glob::glob;
+use std::path::Path;
+
+/// List every directory data discovery considered and what it found there,
+/// without parsing any transcripts. With `anonymize`, each directory is shown
+/// as a pseudonym.
+pub fn show_paths(json: bool, anonymize: bool) -> Result<()> {
+    let probes = DataLoader::probe_claude_paths();
+    let env_resolved = probes.iter().any(|p| p.used && p.origin == PathOrigin::Env);
+    // A projects folder keeps its name under its config directory's pseudonym,
+    // which is also what `source_root` shows in reports
+    let shown = |path: &Path| match path.parent() {
+        _ if !anonymize => path.display().to_string(),
+        Some(parent) if path.file_name().is_some_and(|name| name == "projects") => {
+            format!("{}/projects", pseudonym("source", &parent.display().to_string()))
+        }
+        _ => pseudonym("source", &path.display().to_string()),
+    };
+
+    let rows: Vec<_> = probes.iter()
+        .map(|probe| {
+            let transcripts = probe.projects_dir.as_deref().map(count_jsonl);
+            (probe, status(probe, env_resolved), transcripts)
+        })
+        .collect();
+
+    if json {
+        let rows: Vec<_> = rows.iter()
+            .map(|(probe, status, transcripts)| serde_json::json!({
+                "source": origin_label(probe.origin),
+                "path": shown(&probe.path),
+                "exists": probe.exists,
+                "has_projects_dir": probe.path.join("projects").is_dir(),
+                "projects_dir": probe.projects_dir.as_deref().map(shown),
+                "jsonl_files": transcripts,
+                "used": probe.used,
+                "status": status
+            }))
+            .collect();
+        outln!("{}", to_json_string(&rows)?)?;
+        return Ok(());
+    }
+
+    if std::env::var_os("CLAUDE_CONFIG_DIR").is_none() {
+        outln!("CLAUDE_CONFIG_DIR is not set; checking the default locations\n")?;
+    }
+    for (probe, status, transcripts) in &rows {
+        let yes_no = |b: bool| if b { "yes" } else { "no" };
+        outln!("{}  ({})", shown(&probe.path), origin_label(probe.origin))?;
+        outln!(
+            "  exists: {}, projects folder: {}",
+            yes_no(probe.exists),
+            yes_no(probe.path.join("projects").is_dir())
+        )?;
+        if let (Some(dir), Some(count)) = (&probe.projects_dir, transcripts) {
+            outln!("  {} .jsonl file(s) in {}", format_number(*count as u64), shown(dir))?;
+        }
+        outln!("  {}", status)?;
+    }
+
+    if !probes.iter().any(|p| p.used) {
+        outln!(
+            "\nNo Claude data directory found. Set CLAUDE_CONFIG_DIR to the directory \
+             holding your Claude Code transcripts (comma-separated for several)."
+        )?;
+    }
+    Ok(())
+}
+
+fn origin_label(origin: PathOrigin) -> &'static str {
+    match origin {
+        PathOrigin::Env => "CLAUDE_CONFIG_DIR",
+        PathOrigin::Default => "default",
+    }
+}
+
+fn status(probe: &PathProbe, env_resolved: bool) -> &'static str {
+    if probe.used {
+        "used"
+    } else if !probe.exists {
+        "not found"
+    } else if probe.projects_dir.is_none() {
+        "no transcripts"
+    } else if probe.origin == PathOrigin::Default && env_resolved {
+        "skipped, CLAUDE_CONFIG_DIR takes precedence"
+    } else {
+        "duplicate of an earlier directory"
+    }
+}
+
+/// Transcripts the loader's `**/*.jsonl` glob would read under `dir`
+fn count_jsonl(dir: &Path) -> usize {
+    dir.join("**/*.jsonl")
+        .to_str()
+        .and_then(|pattern| glob(pattern).ok())
+        .map_or(0, |matches| matches.filter_map(|entry| entry.ok()).count())
+}
@@ -0,0 +1,121 @@
+use crate::currency::Currency;
+use crate::data_loader::{DataLoader, LoadOptions};
+use crate::models::TokenUsage;
+use crate::util::{format_number, outln, print_report_footer, print_token_definition_note, to_json_string, token_total};
+use anyhow::{bail, Result};
+use chrono::{Datelike, Local};
+use std::collections::BTreeMap;
+
+/// Tokens and actual cost of one model, with what the same tokens would cost
+/// at the target model's rates
+struct SimulatedModel {
+    tokens: TokenUsage,
+    actual: f64,
+    simulated: f64,
+}
+
+/// Re-price every model's tokens at `target`'s rates and compare with what was
+/// actually spent. Token counts are the same cache-adjusted ones every other
+/// report uses; the long-context premium isn't applied to the simulated cost
+/// since tokens are summed per day rather than per request.
+pub fn simulate_model(
+    target: &str,
+    this_month: bool,
+    json: bool,
+    read_stdin: bool,
+    currency: &Currency,
+    options: &LoadOptions,
+) -> Result<()> {
+    let loader = DataLoader::for_input(read_stdin, options)?;
+    let pricing = loader.pricing();
+    if pricing.get_pricing(target).is_none() {
+        bail!(
+            "No pricing for model '{}'. Known models: {}. Price others with --price MODEL=IN,OUT",
+            target,
+            pricing.model_ids().join(", ")
+        );
+    }
+
+    let mut stats = loader.load_all_usage()?;
+    if this_month {
+        let today = Local::now().date_naive();
+        stats = stats.between(today.with_day(1).unwrap_or(today), today);
+    }
+    if stats.is_empty() {
+        eprintln!("{}", loader.empty_state_message());
+        if !json {
+            return Ok(());
+        }
+    }
+
+    let mut models: BTreeMap<&str, SimulatedModel> = BTreeMap::new();
+    for row in &stats.daily_models {
+        let model = models.entry(row.model.as_str()).or_insert_with(|| SimulatedModel {
+            tokens: TokenUsage::default(),
+            actual: 0.0,
+            simulated: 0.0,
+        });
+        model.tokens.add(&row.tokens);
+        model.actual += row.total_cost;
+        model.simulated += pricing.calculate_cost(target, &row.tokens, false);
+    }
+
+    // Most expensive first
+    let mut models: Vec<_> = models.into_iter().collect();
+    models.sort_by(|(_, a), (_, b)| b.actual.total_cmp(&a.actual));
+    let actual: f64 = models.iter().map(|(_, m)| m.actual).sum();
+    let simulated: f64 = models.iter().map(|(_, m)| m.simulated).sum();
+    let difference = simulated - actual;
+    let pct_change = (actual > 0.0).then(|| difference / actual * 100.0);
+
+    if json {
+        let rows: Vec<_> = models.iter()
+            .map(|(model, m)| serde_json::json!({
+                "model": model,
+                "tokens": token_total(&m.tokens),
+                "actual_cost": m.actual,
+                "simulated_cost": m.simulated
+            }))
+            .collect();
+        let output = serde_json::json!({
+            "target_model": target,
+            "period": if this_month { "this_month" } else { "all_time" },
+            "actual_cost": actual,
+            "simulated_cost": simulated,
+            "difference": difference,
+            "pct_change": pct_change,
+            "models": rows
+        });
+        outln!("{}", to_json_string(&output)?)?;
+        return Ok(());
+    }
+
+    let period = if this_month { "this month" } else { "all history" };
+    outln!("Re-pricing {} at {} rates, token counts unchanged\n", period, target)?;
+    let name_width = models.iter().map(|(model, _)| model.len()).max().unwrap_or(0).max(5);
+    outln!("{:<name_width$}  {:>15}  {:>11}  {:>11}", "Model", "Tokens", "Actual", "Simulated")?;
+    for (model, m) in &models {
+        outln!(
+            "{:<name_width$}  {:>15}  {:>11}  {:>11}",
+            model,
+            format_number(token_total(&m.tokens)),
+            currency.format(m.actual, 2),
+            currency.format(m.simulated, 2)
+        )?;
+    }
+    outln!(
+        "\n{:<name_width$}  {:>15}  {:>11}  {:>11}",
+        "Total",
+        format_number(token_total(&stats.total_tokens)),
+        currency.format(actual, 2),
+        currency.format(simulated, 2)
+    )?;
+
+    let sign = if difference < 0.0 { "-" } else { "+" };
+    let pct = pct_change.map_or_else(String::new, |pct| format!(" ({:+.1}%)", pct));
+    outln!("Difference: {}{}{}", sign, currency.format(difference.abs(), 2), pct)?;
+    print_token_definition_note()?;
+    print_report_footer(&stats)?;
+
+    Ok(())
+}
@@ -1,11 +1,28 @@
-use crate::data_loader::DataLoader;
+use crate::cli::Plan;
+use crate::currency::Currency;
+use crate::data_loader::{DataLoader, LoadOptions};
+use crate::models::{SessionUsage, UsageStats};
+use crate::util::{format_number, outln, pseudonym, to_json, to_json_string, token_definition, token_total};
 use anyhow::Result;
 use chrono::{DateTime, Duration, Local, Utc};
 use serde::Deserialize;
-use std::io::{self, Read};
+use std::io::{self, IsTerminal, Read};
+use tracing::warn;
+
+/// Burn rates at or above these are shown in yellow and red respectively
+const TOKENS_PER_HOUR_WARN: f64 = 2_000_000.0;
+const TOKENS_PER_HOUR_HIGH: f64 = 10_000_000.0;
+
+/// Plan usage at or above these percentages is shown in yellow and red
+const PLAN_PCT_WARN: f64 = 80.0;
+const PLAN_PCT_HIGH: f64 = 100.0;
+
+/// Shape version of `--json` output, reported in its `version` field
+const STATUSLINE_JSON_VERSION: u32 = 1;
 
 #[derive(Debug, Deserialize)]
 pub struct HookInput {
+    pub session_id: Option<String>,
     pub transcript_path: String,
     #[allow(dead_code)]
     pub cwd: String,
@@ -30,17 +47,132 @@ pub struct WorkspaceInfo {
     pub project_dir: String,
 }
 
-pub fn show_statusline(read_stdin: bool) -> Result<()> {
-    let loader = DataLoader::new()?;
-    let stats = loader.load_all_usage()?;
+pub fn show_statusline(
+    read_stdin: bool,
+    burn: BurnWindow,
+    plan: Option<Plan>,
+    color: bool,
+    currency: &Currency,
+    options: &LoadOptions,
+) -> Result<()> {
+    let hook_data = if read_stdin {
+        read_hook_input(options.anonymize)?
+    } else {
+        None
+    };
+
+    let loader = DataLoader::new(options)?.with_modified_since(earliest_needed(Utc::now(), burn));
+    let stats = with_hook_transcript(loader, hook_data.as_ref()).load_all_usage()?;
+
+    outln!("{}", format_statusline(&stats, hook_data.as_ref(), burn, plan, color, currency))?;
+
+    Ok(())
+}
+
+/// Keep running and reprint the statusline every `interval_secs` seconds or on SIGUSR1.
+/// In JSON mode each tick is printed as a single compact line.
+#[allow(clippy::too_many_arguments)]
+pub async fn watch_statusline(
+    read_stdin: bool,
+    interval_secs: u64,
+    burn: BurnWindow,
+    plan: Option<Plan>,
+    json: bool,
+    color: bool,
+    currency: &Currency,
+    options: &LoadOptions,
+) -> Result<()> {
+    // Hook input is only piped in once, so consume it before the first tick
+    let hook_data = if read_stdin {
+        read_hook_input(options.anonymize)?
+    } else {
+        None
+    };
+    let loader = with_hook_transcript(DataLoader::new(options)?.with_tailing(), hook_data.as_ref());
+
+    let mut ticker = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+    let mut signals = WatchSignals::new()?;
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            event = signals.next() => {
+                if event == WatchEvent::Terminate {
+                    break;
+                }
+            }
+        }
+
+        // A transcript being rotated mid-read shouldn't end a long-running watcher.
+        // Loading reads files and may briefly sleep waiting for the active
+        // transcript's last line, so keep it off the async worker.
+        let stats = match tokio::task::block_in_place(|| loader.load_all_usage()) {
+            Ok(stats) => stats,
+            Err(e) => {
+                warn!("Skipping statusline refresh: {:#}", e);
+                continue;
+            }
+        };
+        if json {
+            let output = format_statusline_json(&stats, hook_data.as_ref(), burn, plan);
+            outln!("{}", to_json(&output, false)?)?;
+        } else {
+            outln!("{}", format_statusline(&stats, hook_data.as_ref(), burn, plan, color, currency))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// With `anonymize`, the hook's session id is replaced by the same pseudonym
+/// the loaded sessions carry, so the current session is still found
+fn read_hook_input(anonymize: bool) -> Result<Option<HookInput>> {
+    // Nothing is piped in when run by hand, and reading would wait for EOF
+    if io::stdin().is_terminal() {
+        return Ok(None);
+    }
+    let mut buffer = String::new();
+    io::stdin().read_to_string(&mut buffer)?;
+    let mut hook = serde_json::from_str::<HookInput>(&buffer).ok();
+    if let Some(hook) = hook.as_mut().filter(|_| anonymize) {
+        hook.session_id = hook_session_id(hook).map(|id| pseudonym("session", &id));
+    }
+    Ok(hook)
+}
 
-    // Parse stdin if available (just to validate format)
-    if read_stdin {
-        let mut buffer = String::new();
-        io::stdin().read_to_string(&mut buffer)?;
-        let _ = serde_json::from_str::<HookInput>(&buffer);
+/// Retry torn last lines in the transcript the hook was invoked for, which
+/// Claude is likely still writing
+fn with_hook_transcript(loader: DataLoader, hook_data: Option<&HookInput>) -> DataLoader {
+    match hook_data {
+        Some(hook) => loader.with_active_file(std::path::Path::new(&hook.transcript_path)),
+        None => loader,
     }
+}
 
+/// The hook's session id, or else its transcript's file name
+fn hook_session_id(hook: &HookInput) -> Option<String> {
+    hook.session_id.clone().or_else(|| {
+        std::path::Path::new(&hook.transcript_path)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_string())
+    })
+}
+
+/// Session the hook was invoked for, by its id or else the transcript's file name
+fn current_session<'a>(stats: &'a UsageStats, hook_data: Option<&HookInput>) -> Option<&'a SessionUsage> {
+    let session_id = hook_session_id(hook_data?)?;
+    stats.sessions.iter().find(|s| s.session_id == session_id)
+}
+
+fn format_statusline(
+    stats: &UsageStats,
+    hook_data: Option<&HookInput>,
+    burn: BurnWindow,
+    plan: Option<Plan>,
+    color: bool,
+    currency: &Currency,
+) -> String {
     // Calculate today's stats
     let today = Local::now().date_naive();
     let today_usage = stats.daily.iter().find(|d| d.date == today);
@@ -48,75 +180,358 @@ pub fn show_statusline(read_stdin: bool) -> Result<()> {
 
     // Get current 5-hour block stats
     let now = Utc::now();
-    let hours_since_epoch = now.timestamp() / 3600;
-    let block_start_hours = (hours_since_epoch / 5) * 5;
-    let block_start = DateTime::<Utc>::from_timestamp(block_start_hours * 3600, 0).unwrap_or(now);
-
-    let block_sessions: Vec<_> = stats
-        .sessions
-        .iter()
-        .filter(|s| s.last_activity >= block_start)
-        .collect();
+    let block_start = block_start(now);
 
-    let block_cost: f64 = block_sessions.iter().map(|s| s.total_cost).sum();
+    let (_, block_cost) = block_usage(stats, block_start);
 
     // Calculate time remaining in block
     let block_end = block_start + Duration::hours(5);
-    let remaining = block_end - now;
-    let hours_remaining = remaining.num_hours();
-    let minutes_remaining = remaining.num_minutes() % 60;
-
-    // Always use light green color
-    let color = "\x1b[92m";  // Light green
-    
-    println!(
-        "{color}${:.0}/${:.0} ({:02}:{:02} left)\x1b[0m",
-        block_cost, today_cost, hours_remaining, minutes_remaining
+    let remaining = block_remaining(block_end, now);
+    let time_left = if remaining.is_zero() {
+        "block ended".to_string()
+    } else {
+        format!("{:02}:{:02} left", remaining.num_hours(), remaining.num_minutes() % 60)
+    };
+
+    let (tokens_per_hour, _) = burn_rate(stats, now, burn);
+
+    // Light green when color is enabled; the burn rate gets its own threshold color
+    let (start, reset) = if color { ("\x1b[92m", "\x1b[0m") } else { ("", "") };
+    let burn_start = if !color {
+        ""
+    } else if tokens_per_hour >= TOKENS_PER_HOUR_HIGH {
+        "\x1b[91m"
+    } else if tokens_per_hour >= TOKENS_PER_HOUR_WARN {
+        "\x1b[93m"
+    } else {
+        start
+    };
+
+    let mut line = format!(
+        "{start}{}/{} ({}){reset} {burn_start}{} tok/h{reset}",
+        currency.format(block_cost, 0),
+        currency.format(today_cost, 0),
+        time_left,
+        format_number(tokens_per_hour as u64)
     );
+    if let Some(session) = current_session(stats, hook_data) {
+        line.push_str(&format!(" {start}{} session tok{reset}", format_number(token_total(&session.tokens))));
+    }
+    if let Some(plan) = plan {
+        let pct = plan_percent(plan, block_cost);
+        let plan_start = if !color {
+            ""
+        } else if pct >= PLAN_PCT_HIGH {
+            "\x1b[91m"
+        } else if pct >= PLAN_PCT_WARN {
+            "\x1b[93m"
+        } else {
+            start
+        };
+        line.push_str(&format!(" {plan_start}~{:.0}% of {} (est.){reset}", pct, plan_name(plan)));
+    }
+    line
+}
 
-    Ok(())
+/// Rough cost of the usage Pro allows in one 5-hour block. Anthropic doesn't
+/// publish this and it varies with load and model, so any percentage derived
+/// from it is an estimate.
+const PRO_BLOCK_LIMIT: f64 = 18.0;
+
+/// Rough cost of the usage each plan allows in one 5-hour block: Max 5x and
+/// Max 20x are sold as five and twenty times Pro's usage
+fn plan_block_limit(plan: Plan) -> f64 {
+    let multiple = match plan {
+        Plan::Pro => 1.0,
+        Plan::Max5 => 5.0,
+        Plan::Max20 => 20.0,
+    };
+    PRO_BLOCK_LIMIT * multiple
 }
 
-#[allow(dead_code)]
-pub fn show_statusline_json(read_stdin: bool) -> Result<()> {
-    let loader = DataLoader::new()?;
-    let stats = loader.load_all_usage()?;
+fn plan_name(plan: Plan) -> &'static str {
+    match plan {
+        Plan::Pro => "pro",
+        Plan::Max5 => "max5",
+        Plan::Max20 => "max20",
+    }
+}
+
+/// Estimated share of the plan's block quota that `block_cost` represents
+fn plan_percent(plan: Plan, block_cost: f64) -> f64 {
+    block_cost / plan_block_limit(plan) * 100.0
+}
+
+/// Start of the 5-hour block containing `now`
+fn block_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    let hours_since_epoch = now.timestamp() / 3600;
+    let block_start_hours = (hours_since_epoch / 5) * 5;
+    DateTime::<Utc>::from_timestamp(block_start_hours * 3600, 0).unwrap_or(now)
+}
+
+/// Tokens and cost logged since `block_start`, from the hourly buckets so
+/// sessions that began before the block only count what they used in it
+fn block_usage(stats: &UsageStats, block_start: DateTime<Utc>) -> (u64, f64) {
+    stats.hourly.iter()
+        .filter(|h| h.hour >= block_start)
+        .fold((0u64, 0.0), |(tokens, cost), h| {
+            (tokens.saturating_add(token_total(&h.tokens)), cost + h.total_cost)
+        })
+}
+
+/// Oldest moment the statusline reports on: local midnight, the block start
+/// or the start of the burn window, whichever is earliest
+fn earliest_needed(now: DateTime<Utc>, burn: BurnWindow) -> DateTime<Utc> {
+    let midnight = Local::now()
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|t| t.and_local_timezone(Local).earliest())
+        .map_or(now - Duration::days(1), |t| t.with_timezone(&Utc));
+    let burn_start = now - Duration::hours(i64::from(burn.hours) + 1);
+    midnight.min(block_start(now)).min(burn_start)
+}
+
+/// Time left in the block ending at `block_end`, clamped at zero so a
+/// crossed boundary never shows negative minutes
+fn block_remaining(block_end: DateTime<Utc>, now: DateTime<Utc>) -> Duration {
+    (block_end - now).max(Duration::zero())
+}
+
+/// Hourly buckets averaged into the burn rate
+#[derive(Clone, Copy)]
+pub struct BurnWindow {
+    pub hours: u32,
+    /// Average the hours before the current one, so a big request in the
+    /// in-progress hour doesn't spike the rate
+    pub completed_only: bool,
+}
+
+impl BurnWindow {
+    /// JSON label for the averaging mode
+    fn mode(self) -> &'static str {
+        if self.completed_only { "completed_hours" } else { "including_current_hour" }
+    }
+}
+
+/// Average tokens and cost per hour over `burn.hours` hourly buckets: the
+/// current hour and the ones before it, or only completed hours. A window
+/// including the current hour is averaged over the time it has actually
+/// covered so far, since that hour's bucket is only partly filled.
+fn burn_rate(stats: &UsageStats, now: DateTime<Utc>, burn: BurnWindow) -> (f64, f64) {
+    let current_hour = now.timestamp().div_euclid(3600) * 3600;
+    let current_hour = DateTime::<Utc>::from_timestamp(current_hour, 0).unwrap_or(now);
+    let (window_start, window_end) = if burn.completed_only {
+        (current_hour - Duration::hours(i64::from(burn.hours)), current_hour)
+    } else {
+        (current_hour - Duration::hours(i64::from(burn.hours) - 1), current_hour + Duration::hours(1))
+    };
+
+    let (tokens, cost) = stats.hourly.iter()
+        .filter(|h| h.hour >= window_start && h.hour < window_end)
+        .fold((0u64, 0.0), |(tokens, cost), h| {
+            (tokens.saturating_add(token_total(&h.tokens)), cost + h.total_cost)
+        });
+    let hours = if burn.completed_only {
+        f64::from(burn.hours)
+    } else {
+        // At least a minute, so a request just past the hour doesn't divide by ~0
+        (now - window_start).num_seconds().max(60) as f64 / 3600.0
+    };
+    (tokens as f64 / hours, cost / hours)
+}
+
+#[derive(PartialEq)]
+enum WatchEvent {
+    #[cfg_attr(not(unix), allow(dead_code))]
+    Refresh,
+    Terminate,
+}
+
+#[cfg(unix)]
+struct WatchSignals {
+    refresh: tokio::signal::unix::Signal,
+    terminate: tokio::signal::unix::Signal,
+}
+
+#[cfg(unix)]
+impl WatchSignals {
+    fn new() -> Result<Self> {
+        use tokio::signal::unix::{signal, SignalKind};
+        Ok(Self {
+            refresh: signal(SignalKind::user_defined1())?,
+            terminate: signal(SignalKind::terminate())?,
+        })
+    }
+
+    async fn next(&mut self) -> WatchEvent {
+        tokio::select! {
+            _ = self.refresh.recv() => WatchEvent::Refresh,
+            _ = self.terminate.recv() => WatchEvent::Terminate,
+            _ = tokio::signal::ctrl_c() => WatchEvent::Terminate,
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct WatchSignals;
+
+#[cfg(not(unix))]
+impl WatchSignals {
+    fn new() -> Result<Self> {
+        Ok(Self)
+    }
 
+    async fn next(&mut self) -> WatchEvent {
+        let _ = tokio::signal::ctrl_c().await;
+        WatchEvent::Terminate
+    }
+}
+
+pub fn show_statusline_json(
+    read_stdin: bool,
+    burn: BurnWindow,
+    plan: Option<Plan>,
+    options: &LoadOptions,
+) -> Result<()> {
     // Parse stdin if available
     let hook_data = if read_stdin {
-        let mut buffer = String::new();
-        io::stdin().read_to_string(&mut buffer)?;
-        serde_json::from_str::<HookInput>(&buffer).ok()
+        read_hook_input(options.anonymize)?
     } else {
         None
     };
 
+    let loader = DataLoader::new(options)?.with_modified_since(earliest_needed(Utc::now(), burn));
+    let stats = with_hook_transcript(loader, hook_data.as_ref()).load_all_usage()?;
+
+    let output = format_statusline_json(&stats, hook_data.as_ref(), burn, plan);
+    outln!("{}", to_json_string(&output)?)?;
+
+    Ok(())
+}
+
+/// Print the JSON Schema for `statusline --json` output
+pub fn show_statusline_schema() -> Result<()> {
+    outln!("{}", to_json_string(&statusline_json_schema())?)?;
+    Ok(())
+}
+
+/// Schema for the object built by `format_statusline_json`; update both
+/// together and bump `STATUSLINE_JSON_VERSION` on breaking changes
+fn statusline_json_schema() -> serde_json::Value {
+    let usd = |description: &str| serde_json::json!({ "type": "number", "description": format!("{} (USD)", description) });
+    let count = |description: &str| serde_json::json!({ "type": "integer", "minimum": 0, "description": description });
+    let timestamp = |description: &str| serde_json::json!({ "type": "string", "format": "date-time", "description": description });
+
+    serde_json::json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "cc-monitor statusline",
+        "type": "object",
+        "required": ["version", "model", "token_definition", "session", "today", "block", "burn_rate", "plan"],
+        "properties": {
+            "version": { "const": STATUSLINE_JSON_VERSION, "description": "Format version, bumped on breaking changes" },
+            "model": { "type": ["string", "null"], "description": "Model display name from the hook input" },
+            "token_definition": {
+                "enum": ["includes_cache_read", "excludes_cache_read"],
+                "description": "Whether token totals count cache reads (see --exclude-cache-read)"
+            },
+            "session": {
+                "type": ["object", "null"],
+                "description": "Session named by the hook input, if found",
+                "properties": {
+                    "id": { "type": "string" },
+                    "tokens": {
+                        "type": "object",
+                        "properties": {
+                            "input_tokens": count("Uncached input tokens"),
+                            "output_tokens": count("Output tokens"),
+                            "cache_creation_input_tokens": count("Tokens written to the prompt cache"),
+                            "cache_read_input_tokens": count("Tokens read from the prompt cache")
+                        }
+                    },
+                    "total_tokens": count("Token total for the session"),
+                    "cost": usd("Session cost")
+                }
+            },
+            "today": {
+                "type": "object",
+                "properties": {
+                    "cost": usd("Cost so far today"),
+                    "tokens": count("Tokens so far today"),
+                    "date": { "type": "string", "format": "date", "description": "Local date" }
+                }
+            },
+            "block": {
+                "type": "object",
+                "description": "Current 5-hour billing block",
+                "properties": {
+                    "cost": usd("Cost of sessions active in the block"),
+                    "tokens": count("Tokens of sessions active in the block"),
+                    "start": timestamp("Block start"),
+                    "end": timestamp("Block end"),
+                    "remaining_minutes": { "type": "integer", "minimum": 0, "description": "Minutes until the block ends; 0 once it has ended" }
+                }
+            },
+            "burn_rate": {
+                "type": "object",
+                "properties": {
+                    "tokens_per_hour": { "type": "number", "description": "Tokens per hour over the window" },
+                    "cost_per_hour": usd("Cost per hour over the window"),
+                    "window_hours": count("Hours averaged (--burn-window)"),
+                    "mode": {
+                        "type": "string",
+                        "enum": ["including_current_hour", "completed_hours"],
+                        "description": "Whether the in-progress hour is averaged in (--completed-hours leaves it out)"
+                    }
+                }
+            },
+            "plan": {
+                "type": ["object", "null"],
+                "description": "Estimated share of the block quota used, with --plan",
+                "properties": {
+                    "name": { "enum": ["pro", "max5", "max20"] },
+                    "block_cost_limit": usd("Rough cost the plan allows per block; not published, so an estimate"),
+                    "percent_used": { "type": "number", "minimum": 0, "description": "Block cost as a percentage of block_cost_limit" },
+                    "estimate": { "const": true, "description": "Always true: the limits are approximations" }
+                }
+            }
+        }
+    })
+}
+
+fn format_statusline_json(
+    stats: &UsageStats,
+    hook_data: Option<&HookInput>,
+    burn: BurnWindow,
+    plan: Option<Plan>,
+) -> serde_json::Value {
     // Calculate today's stats
     let today = Local::now().date_naive();
     let today_usage = stats.daily.iter().find(|d| d.date == today);
     let today_cost = today_usage.map(|u| u.total_cost).unwrap_or(0.0);
-    let today_tokens = today_usage.map(|u| u.tokens.total()).unwrap_or(0);
+    let today_tokens = today_usage.map(|u| token_total(&u.tokens)).unwrap_or(0);
 
     // Block calculations
     let now = Utc::now();
-    let hours_since_epoch = now.timestamp() / 3600;
-    let block_start_hours = (hours_since_epoch / 5) * 5;
-    let block_start = DateTime::<Utc>::from_timestamp(block_start_hours * 3600, 0).unwrap_or(now);
-
-    let block_sessions: Vec<_> = stats
-        .sessions
-        .iter()
-        .filter(|s| s.last_activity >= block_start)
-        .collect();
+    let block_start = block_start(now);
 
-    let block_cost: f64 = block_sessions.iter().map(|s| s.total_cost).sum();
-    let block_tokens: u64 = block_sessions.iter().map(|s| s.tokens.total()).sum();
+    let (block_tokens, block_cost) = block_usage(stats, block_start);
 
     let block_end = block_start + Duration::hours(5);
-    let remaining_minutes = (block_end - now).num_minutes();
+    let remaining_minutes = block_remaining(block_end, now).num_minutes();
+    let (tokens_per_hour, cost_per_hour) = burn_rate(stats, now, burn);
 
-    let output = serde_json::json!({
-        "model": hook_data.as_ref().map(|h| &h.model.display_name),
+    let session = current_session(stats, hook_data);
+
+    serde_json::json!({
+        "version": STATUSLINE_JSON_VERSION,
+        "model": hook_data.map(|h| &h.model.display_name),
+        "token_definition": token_definition(),
+        "session": session.map(|s| serde_json::json!({
+            "id": s.session_id,
+            "tokens": s.tokens,
+            "total_tokens": token_total(&s.tokens),
+            "cost": s.total_cost
+        })),
         "today": {
             "cost": today_cost,
             "tokens": today_tokens,
@@ -128,10 +543,75 @@ pub fn show_statusline_json(read_stdin: bool) -> Result<()> {
             "start": block_start.to_rfc3339(),
             "end": block_end.to_rfc3339(),
             "remaining_minutes": remaining_minutes
-        }
-    });
+        },
+        "burn_rate": {
+            "tokens_per_hour": tokens_per_hour,
+            "cost_per_hour": cost_per_hour,
+            "window_hours": burn.hours,
+            "mode": burn.mode()
+        },
+        "plan": plan.map(|plan| serde_json::json!({
+            "name": plan_name(plan),
+            "block_cost_limit": plan_block_limit(plan),
+            "percent_used": plan_percent(plan, block_cost),
+            "estimate": true
+        }))
+    })
+}
 
-    println!("{}", serde_json::to_string_pretty(&output)?);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_loader::test_support::{aggregate, entry, usage};
+    use chrono::TimeZone;
 
-    Ok(())
-}
\ No newline at end of file
+    /// Stats for Sonnet 4 requests of `output_tokens` each, at $15/Mtok
+    fn stats(requests: &[(&str, DateTime<Utc>, u64)]) -> UsageStats {
+        let entries = requests.iter()
+            .map(|&(session, timestamp, output_tokens)| entry(session, timestamp, usage(0, output_tokens, 0, 0)))
+            .collect();
+        aggregate(&LoadOptions::default(), entries)
+    }
+
+    #[test]
+    fn plan_limits_scale_with_the_plan() {
+        assert_eq!(plan_block_limit(Plan::Max5), 5.0 * plan_block_limit(Plan::Pro));
+        assert_eq!(plan_block_limit(Plan::Max20), 20.0 * plan_block_limit(Plan::Pro));
+    }
+
+    #[test]
+    fn burn_rate_divides_by_the_time_the_window_covers() {
+        let now = Utc.with_ymd_and_hms(2025, 6, 1, 12, 30, 0).unwrap();
+        let stats = stats(&[
+            ("a", now - Duration::hours(2), 300_000),
+            ("a", now - Duration::hours(1), 300_000),
+            ("a", now - Duration::minutes(10), 150_000),
+        ]);
+
+        // 10:00 to 12:30: all three entries over two and a half hours
+        let burn = BurnWindow { hours: 3, completed_only: false };
+        let (tokens_per_hour, cost_per_hour) = burn_rate(&stats, now, burn);
+        assert!((tokens_per_hour - 750_000.0 / 2.5).abs() < 1e-6);
+        assert!((cost_per_hour - 750_000.0 * 15e-6 / 2.5).abs() < 1e-9);
+
+        // 09:00 to 12:00: the two finished hours with usage over all three
+        let burn = BurnWindow { hours: 3, completed_only: true };
+        let (tokens_per_hour, _) = burn_rate(&stats, now, burn);
+        assert!((tokens_per_hour - 600_000.0 / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn block_usage_only_counts_hours_inside_the_block() {
+        let block = block_start(Utc.with_ymd_and_hms(2025, 6, 1, 12, 30, 0).unwrap());
+        let stats = stats(&[
+            // One session running across the block boundary
+            ("a", block - Duration::minutes(30), 1_000_000),
+            ("a", block + Duration::minutes(30), 100_000),
+            ("b", block + Duration::hours(2), 10_000),
+        ]);
+
+        let (tokens, cost) = block_usage(&stats, block);
+        assert_eq!(tokens, 110_000);
+        assert!((cost - 110_000.0 * 15e-6).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,279 @@
+use crate::currency::Currency;
+use crate::data_loader::{DataLoader, LoadOptions};
+use crate::models::{CostBreakdown, TokenUsage, UsageStats};
+use crate::util::{effective_rate_per_mtok, format_number, output, output_is_terminal, outln, print_report_footer, print_token_definition_note, to_json, to_json_string, to_json_value, token_definition, token_total};
+use crate::template::Template;
+use anyhow::{Context, Result};
+use chrono::Local;
+use crossterm::{
+    cursor::MoveTo,
+    execute,
+    terminal::{Clear, ClearType},
+};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::Path;
+use tracing::warn;
+
+/// Exit status for `--fail-over`, distinct from the generic error status of 1
+const OVER_BUDGET_EXIT_CODE: i32 = 2;
+
+/// A session counts as active if it logged usage this recently
+const ACTIVE_WINDOW_MINUTES: i64 = 5;
+
+pub fn show_summary(
+    json: bool,
+    read_stdin: bool,
+    fail_over: Option<f64>,
+    by_source: bool,
+    template: Option<&Path>,
+    currency: &Currency,
+    options: &LoadOptions,
+) -> Result<()> {
+    // Fail on template syntax before spending time on a load
+    let template = template
+        .map(|path| -> Result<_> {
+            let source = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read template {}", path.display()))?;
+            Template::parse(&source).with_context(|| format!("Invalid template {}", path.display()))
+        })
+        .transpose()?;
+    
+    let loader = DataLoader::for_input(read_stdin, options)?;
+    let stats = loader.load_all_usage()?;
+
+    if stats.is_empty() {
+        eprintln!("{}", loader.empty_state_message());
+        if !json && template.is_none() {
+            return Ok(());
+        }
+    }
+
+    if let Some(template) = template {
+        output().write_all(template.render(&to_json_value(&stats)?).as_bytes())?;
+    } else if json {
+        outln!("{}", to_json_string(&summary_json(&stats, by_source))?)?;
+    } else {
+        print_text_summary(&stats, by_source, currency)?;
+    }
+
+    if let Some(limit) = fail_over {
+        let month_cost = stats.get_month_stats().map(|m| m.total_cost).unwrap_or_default();
+        if month_cost > limit {
+            eprintln!(
+                "This month's cost ${:.2} exceeds the --fail-over limit of ${:.2}",
+                month_cost, limit
+            );
+            std::process::exit(OVER_BUDGET_EXIT_CODE);
+        }
+    }
+
+    Ok(())
+}
+
+/// Reprint the summary every `interval_secs` seconds until Ctrl-C. Text mode
+/// clears the screen between frames when writing to a terminal; JSON mode
+/// emits one object per line. A failed reload is logged and retried on the
+/// next tick.
+pub async fn watch_summary(
+    json: bool,
+    interval_secs: u64,
+    by_source: bool,
+    currency: &Currency,
+    options: &LoadOptions,
+) -> Result<()> {
+    let loader = DataLoader::new(options)?.with_tailing();
+    let mut ticker = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+    // One listener for the whole watch, spawned so it's registered straight
+    // away and a Ctrl-C pressed mid-reload is still seen on the next pass
+    let mut ctrl_c = tokio::spawn(tokio::signal::ctrl_c());
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = &mut ctrl_c => break,
+        }
+
+        // Loading reads every transcript, so keep it off the async worker
+        let stats = match tokio::task::block_in_place(|| loader.load_all_usage()) {
+            Ok(stats) => stats,
+            Err(e) => {
+                warn!("Skipping summary refresh: {:#}", e);
+                continue;
+            }
+        };
+        if json {
+            outln!("{}", to_json(&summary_json(&stats, by_source), false)?)?;
+        } else {
+            if output_is_terminal() {
+                execute!(io::stdout(), Clear(ClearType::All), MoveTo(0, 0))?;
+            }
+            print_text_summary(&stats, by_source, currency)?;
+        }
+    }
+
+    Ok(())
+}
+
+struct Window {
+    key: &'static str,
+    label: &'static str,
+    tokens: TokenUsage,
+    cost: f64,
+    cost_breakdown: CostBreakdown,
+}
+
+fn windows(stats: &UsageStats) -> [Window; 6] {
+    let today = stats.get_today_stats();
+    let month = stats.get_month_stats();
+
+    [
+        Window {
+            key: "today",
+            label: "Today",
+            tokens: today.map(|d| d.tokens.clone()).unwrap_or_default(),
+            cost: today.map(|d| d.total_cost).unwrap_or_default(),
+            cost_breakdown: today.map(|d| d.cost_breakdown.clone()).unwrap_or_default(),
+        },
+        rolling_window(stats, "last_7_days", "Last 7 Days", 7),
+        rolling_window(stats, "last_30_days", "Last 30 Days", 30),
+        rolling_window(stats, "last_90_days", "Last 90 Days", 90),
+        Window {
+            key: "this_month",
+            label: "This Month",
+            tokens: month.map(|m| m.tokens.clone()).unwrap_or_default(),
+            cost: month.map(|m| m.total_cost).unwrap_or_default(),
+            cost_breakdown: month.map(|m| m.cost_breakdown.clone()).unwrap_or_default(),
+        },
+        Window {
+            key: "all_time",
+            label: "All Time",
+            tokens: stats.total_tokens.clone(),
+            cost: stats.total_cost,
+            cost_breakdown: stats.cost_breakdown.clone(),
+        },
+    ]
+}
+
+fn rolling_window(stats: &UsageStats, key: &'static str, label: &'static str, days: i64) -> Window {
+    let (tokens, cost) = stats.get_rolling_stats(days);
+    Window {
+        key,
+        label,
+        tokens,
+        cost,
+        cost_breakdown: stats.get_rolling_cost_breakdown(days),
+    }
+}
+
+/// All-time usage from one data directory
+struct Source {
+    label: String,
+    sessions: usize,
+    tokens: TokenUsage,
+    cost: f64,
+}
+
+/// Sessions grouped by the data directory they were read from, highest cost first
+fn sources(stats: &UsageStats) -> Vec<Source> {
+    let mut sources: BTreeMap<&str, Source> = BTreeMap::new();
+    for session in &stats.sessions {
+        let label = session.source_root.as_deref().unwrap_or("stdin");
+        let source = sources.entry(label).or_insert_with(|| Source {
+            label: label.to_string(),
+            sessions: 0,
+            tokens: TokenUsage::default(),
+            cost: 0.0,
+        });
+        source.sessions += 1;
+        source.tokens.add(&session.tokens);
+        source.cost += session.total_cost;
+    }
+    
+    let mut sources: Vec<_> = sources.into_values().collect();
+    sources.sort_by(|a, b| b.cost.total_cmp(&a.cost));
+    sources
+}
+
+fn print_text_summary(stats: &UsageStats, by_source: bool, currency: &Currency) -> Result<()> {
+    for window in &windows(stats) {
+        let rate = effective_rate_per_mtok(window.cost, &window.tokens)
+            .map_or_else(|| "-".to_string(), |rate| format!("{}/Mtok", currency.format(rate, 2)));
+        outln!(
+            "{:<12} {:>15} tokens  {:>11}  {:>12}",
+            window.label,
+            format_number(token_total(&window.tokens)),
+            currency.format(window.cost, 2),
+            rate
+        )?;
+    }
+    let active = if stats.active_within(chrono::Duration::minutes(ACTIVE_WINDOW_MINUTES)) { "  (active now)" } else { "" };
+    outln!("\nSessions today: {}{}", stats.sessions_today(), active)?;
+    if by_source {
+        outln!()?;
+        for source in sources(stats) {
+            let plural = if source.sessions == 1 { "" } else { "s" };
+            outln!(
+                "{:<40} {:>5} session{:<1} {:>15} tokens  {:>11}",
+                source.label,
+                source.sessions,
+                plural,
+                format_number(token_total(&source.tokens)),
+                currency.format(source.cost, 2)
+            )?;
+        }
+    }
+    print_token_definition_note()?;
+    print_report_footer(stats)?;
+    Ok(())
+}
+
+fn summary_json(stats: &UsageStats, by_source: bool) -> serde_json::Value {
+    let mut output = serde_json::Map::new();
+    for window in &windows(stats) {
+        output.insert(window.key.to_string(), window_json(window));
+    }
+    let projected = stats
+        .get_month_stats()
+        .and_then(|m| m.projected_cost(Local::now().date_naive()));
+    output["this_month"]["projected_cost"] = serde_json::json!(projected);
+    output.insert("sessions_today".to_string(), serde_json::json!(stats.sessions_today()));
+    output.insert("active".to_string(), serde_json::json!(stats.active_within(chrono::Duration::minutes(ACTIVE_WINDOW_MINUTES))));
+    output.insert("averages".to_string(), serde_json::json!(stats.averages()));
+    output.insert("date_range".to_string(), serde_json::json!(stats.date_range().map(|(first, last, days)| {
+        serde_json::json!({
+            "first_seen": first.to_string(),
+            "last_seen": last.to_string(),
+            "days": days
+        })
+    })));
+    if by_source {
+        let sources: Vec<_> = sources(stats).iter()
+            .map(|source| serde_json::json!({
+                "source": source.label,
+                "sessions": source.sessions,
+                "tokens": token_total(&source.tokens),
+                "cost": source.cost
+            }))
+            .collect();
+        output.insert("by_source".to_string(), serde_json::json!(sources));
+    }
+    output.insert("token_definition".to_string(), serde_json::json!(token_definition()));
+
+    serde_json::Value::Object(output)
+}
+
+fn window_json(window: &Window) -> serde_json::Value {
+    let tokens = &window.tokens;
+    serde_json::json!({
+        "tokens": token_total(tokens),
+        "input_tokens": tokens.input_tokens,
+        "output_tokens": tokens.output_tokens,
+        "cache_creation_tokens": tokens.cache_creation_input_tokens,
+        "cache_read_tokens": tokens.cache_read_input_tokens,
+        "cache_hit_rate": tokens.cache_hit_rate(),
+        "cost": window.cost,
+        "cost_breakdown": window.cost_breakdown,
+        "effective_rate_per_mtok": effective_rate_per_mtok(window.cost, tokens)
+    })
+}
@@ -0,0 +1,53 @@
+use crate::cli::TimeSeriesFormat;
+use crate::data_loader::{DataLoader, LoadOptions};
+use crate::util::{csv_field, output, outln, to_json_string};
+use anyhow::Result;
+use std::fmt::Write as _;
+use std::io::Write;
+
+/// Print one row per (date, model) with its tokens and cost, sorted by date
+/// then model: the long format pandas and ggplot expect. Costs are USD.
+pub fn show_timeseries(format: TimeSeriesFormat, read_stdin: bool, options: &LoadOptions) -> Result<()> {
+    let loader = DataLoader::for_input(read_stdin, options)?;
+    let stats = loader.load_all_usage()?;
+
+    if stats.is_empty() {
+        eprintln!("{}", loader.empty_state_message());
+    }
+
+    match format {
+        TimeSeriesFormat::Json => {
+            let rows: Vec<_> = stats.daily_models.iter()
+                .map(|row| serde_json::json!({
+                    "date": row.date.to_string(),
+                    "model": row.model,
+                    "input_tokens": row.tokens.input_tokens,
+                    "output_tokens": row.tokens.output_tokens,
+                    "cache_creation_tokens": row.tokens.cache_creation_input_tokens,
+                    "cache_read_tokens": row.tokens.cache_read_input_tokens,
+                    "cost": row.total_cost
+                }))
+                .collect();
+            outln!("{}", to_json_string(&rows)?)?;
+        }
+        TimeSeriesFormat::Csv => {
+            let mut out = String::from("date,model,input_tokens,output_tokens,cache_creation_tokens,cache_read_tokens,cost\n");
+            for row in &stats.daily_models {
+                let _ = writeln!(
+                    out,
+                    "{},{},{},{},{},{},{}",
+                    row.date,
+                    csv_field(&row.model),
+                    row.tokens.input_tokens,
+                    row.tokens.output_tokens,
+                    row.tokens.cache_creation_input_tokens,
+                    row.tokens.cache_read_input_tokens,
+                    row.total_cost
+                );
+            }
+            output().write_all(out.as_bytes())?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,178 @@
+use crate::data_loader::{DataLoader, LoadOptions};
+use crate::models::ModelPricing;
+use crate::util::{format_number, outln, to_json_string};
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+
+/// Exit status when a model is off by more than `--tolerance`, distinct from
+/// the generic error status of 1
+const MISMATCH_EXIT_CODE: i32 = 2;
+
+/// Logged against recomputed cost for one model
+#[derive(Default)]
+struct ModelCheck {
+    entries: usize,
+    logged: f64,
+    computed: f64,
+    /// Sum of per-entry absolute differences
+    abs_error: f64,
+    priced: bool,
+}
+
+impl ModelCheck {
+    fn difference(&self) -> f64 {
+        self.computed - self.logged
+    }
+
+    /// Difference as a fraction of the logged cost; `None` when nothing was logged
+    fn relative_difference(&self) -> Option<f64> {
+        if self.logged > 0.0 {
+            Some(self.difference() / self.logged)
+        } else if self.computed == 0.0 {
+            Some(0.0)
+        } else {
+            None
+        }
+    }
+
+    fn mean_abs_error(&self) -> f64 {
+        if self.entries > 0 { self.abs_error / self.entries as f64 } else { 0.0 }
+    }
+
+    fn within(&self, tolerance: f64) -> bool {
+        self.relative_difference().is_some_and(|diff| diff.abs() <= tolerance)
+    }
+}
+
+/// Recompute the cost of every entry that logged a `costUSD` from its tokens
+/// and the pricing table, and report where the two disagree. Entries are
+/// compared as logged, without the resumed-session cache adjustment.
+pub fn verify_costs(json: bool, tolerance: f64, read_stdin: bool, options: &LoadOptions) -> Result<()> {
+    if !tolerance.is_finite() || tolerance < 0.0 {
+        bail!("--tolerance must be a non-negative fraction, e.g. 0.05 for 5%");
+    }
+
+    let loader = DataLoader::for_input(read_stdin, options)?;
+    let entries = loader.load_entries()?;
+    let pricing = loader.pricing();
+
+    let mut models: BTreeMap<String, ModelCheck> = BTreeMap::new();
+    for entry in &entries {
+        let Some(logged) = entry.message.cost_usd else {
+            continue;
+        };
+        if entry.is_synthetic() && !options.include_synthetic {
+            continue;
+        }
+
+        let model = &entry.message.model;
+        let usage = &entry.message.usage;
+        let computed = pricing.calculate_cost(model, usage, ModelPricing::is_long_context(usage));
+        let check = models.entry(model.clone()).or_default();
+        check.entries += 1;
+        check.logged += logged;
+        check.computed += computed;
+        check.abs_error += (computed - logged).abs();
+        check.priced = pricing.get_pricing(model).is_some();
+    }
+
+    let mut total = ModelCheck { priced: true, ..Default::default() };
+    for check in models.values() {
+        total.entries += check.entries;
+        total.logged += check.logged;
+        total.computed += check.computed;
+        total.abs_error += check.abs_error;
+    }
+
+    // Worst offenders first
+    let mut models: Vec<_> = models.into_iter().collect();
+    models.sort_by(|(_, a), (_, b)| b.difference().abs().total_cmp(&a.difference().abs()));
+    let failing: Vec<&str> = models.iter()
+        .filter(|(_, check)| !check.within(tolerance))
+        .map(|(model, _)| model.as_str())
+        .collect();
+
+    if json {
+        let rows: Vec<_> = models.iter()
+            .map(|(model, check)| {
+                let mut row = check_json(check, tolerance);
+                row["model"] = model.as_str().into();
+                row["priced"] = check.priced.into();
+                row
+            })
+            .collect();
+        let output = serde_json::json!({
+            "tolerance": tolerance,
+            "passed": failing.is_empty(),
+            "total": check_json(&total, tolerance),
+            "models": rows
+        });
+        outln!("{}", to_json_string(&output)?)?;
+    } else if total.entries == 0 {
+        eprintln!("No entries with a logged costUSD to verify against");
+        return Ok(());
+    } else {
+        outln!(
+            "{:<32} {:>9} {:>11} {:>11} {:>11} {:>8}",
+            "Model", "Entries", "Logged", "Computed", "Mean error", "Diff"
+        )?;
+        for (model, check) in &models {
+            let status = if !check.priced {
+                "no pricing"
+            } else if check.within(tolerance) {
+                "ok"
+            } else {
+                "over tolerance"
+            };
+            print_row(model, check, status)?;
+        }
+        outln!()?;
+        print_row("Total", &total, "")?;
+
+        outln!()?;
+        if failing.is_empty() {
+            outln!("PASS: every model within {:.1}% of its logged cost", tolerance * 100.0)?;
+        } else {
+            outln!(
+                "FAIL: {} model(s) off by more than {:.1}%: {}",
+                failing.len(),
+                tolerance * 100.0,
+                failing.join(", ")
+            )?;
+        }
+    }
+
+    if !failing.is_empty() {
+        std::process::exit(MISMATCH_EXIT_CODE);
+    }
+    Ok(())
+}
+
+fn print_row(label: &str, check: &ModelCheck, status: &str) -> Result<()> {
+    let diff = check.relative_difference()
+        .map_or_else(|| "-".to_string(), |diff| format!("{:+.1}%", diff * 100.0));
+    let row = format!(
+        "{:<32} {:>9} {:>11} {:>11} {:>11} {:>8}  {}",
+        label,
+        format_number(check.entries as u64),
+        format!("${:.2}", check.logged),
+        format!("${:.2}", check.computed),
+        format!("${:.4}", check.mean_abs_error()),
+        diff,
+        status
+    );
+    outln!("{}", row.trim_end())?;
+    Ok(())
+}
+
+fn check_json(check: &ModelCheck, tolerance: f64) -> serde_json::Value {
+    serde_json::json!({
+        "entries": check.entries,
+        "logged_cost": check.logged,
+        "computed_cost": check.computed,
+        "difference": check.difference(),
+        "relative_difference": check.relative_difference(),
+        "mean_abs_error": check.mean_abs_error(),
+        "within_tolerance": check.within(tolerance)
+    })
+}
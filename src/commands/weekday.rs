@@ -0,0 +1,116 @@
+use crate::currency::Currency;
+use crate::data_loader::{DataLoader, LoadOptions};
+use crate::models::TokenUsage;
+use crate::util::{format_number, glyph, outln, print_report_footer, print_token_definition_note, to_json_string, token_total};
+use anyhow::Result;
+use chrono::{Datelike, Weekday};
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+#[derive(Clone, Default)]
+struct Bucket {
+    active_days: usize,
+    tokens: TokenUsage,
+    cost: f64,
+}
+
+impl Bucket {
+    fn add(&mut self, other: &Bucket) {
+        self.active_days += other.active_days;
+        self.tokens.add(&other.tokens);
+        self.cost += other.cost;
+    }
+
+    /// Average cost over the days that had any usage
+    fn cost_per_active_day(&self) -> f64 {
+        if self.active_days > 0 { self.cost / self.active_days as f64 } else { 0.0 }
+    }
+
+    fn json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "active_days": self.active_days,
+            "tokens": token_total(&self.tokens),
+            "cost": self.cost,
+            "cost_per_active_day": self.cost_per_active_day()
+        })
+    }
+}
+
+pub fn show_weekday(json: bool, read_stdin: bool, currency: &Currency, options: &LoadOptions) -> Result<()> {
+    let loader = DataLoader::for_input(read_stdin, options)?;
+    let stats = loader.load_all_usage()?;
+
+    if stats.is_empty() {
+        eprintln!("{}", loader.empty_state_message());
+        if !json {
+            return Ok(());
+        }
+    }
+
+    // Bucket all history by day of week, Monday first
+    let mut buckets = vec![Bucket::default(); 7];
+    for day in &stats.daily {
+        let bucket = &mut buckets[day.date.weekday().num_days_from_monday() as usize];
+        bucket.active_days += 1;
+        bucket.tokens.add(&day.tokens);
+        bucket.cost += day.total_cost;
+    }
+
+    let (mut weekdays, mut weekend) = (Bucket::default(), Bucket::default());
+    for (i, bucket) in buckets.iter().enumerate() {
+        if i < 5 { weekdays.add(bucket) } else { weekend.add(bucket) }
+    }
+
+    if json {
+        let days: Vec<_> = WEEKDAYS.iter()
+            .zip(&buckets)
+            .map(|(weekday, bucket)| {
+                let mut row = bucket.json();
+                row["weekday"] = weekday.to_string().into();
+                row
+            })
+            .collect();
+        let output = serde_json::json!({
+            "days": days,
+            "weekdays": weekdays.json(),
+            "weekend": weekend.json()
+        });
+        outln!("{}", to_json_string(&output)?)?;
+        return Ok(());
+    }
+
+    outln!(
+        "{:<8} {:>6} {:>15} {:>11} {:>11}",
+        "Day", "Active", "Tokens", "Cost", "Avg/day"
+    )?;
+    for (weekday, bucket) in WEEKDAYS.iter().zip(&buckets) {
+        print_row(&weekday.to_string(), bucket, currency)?;
+    }
+    outln!()?;
+    print_row(&format!("Mon{}Fri", glyph("–", "-")), &weekdays, currency)?;
+    print_row(&format!("Sat{}Sun", glyph("–", "-")), &weekend, currency)?;
+    print_token_definition_note()?;
+    print_report_footer(&stats)?;
+
+    Ok(())
+}
+
+fn print_row(label: &str, bucket: &Bucket, currency: &Currency) -> Result<()> {
+    outln!(
+        "{:<8} {:>6} {:>15} {:>11} {:>11}",
+        label,
+        bucket.active_days,
+        format_number(token_total(&bucket.tokens)),
+        currency.format(bucket.cost, 2),
+        currency.format(bucket.cost_per_active_day(), 2)
+    )?;
+    Ok(())
+}
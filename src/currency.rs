@@ -0,0 +1,107 @@
+use anyhow::Result;
+
+/// Approximate USD exchange rates used when no `--rate` is given
+const BUNDLED_RATES: &[(&str, &str, f64)] = &[
+    ("USD", "$", 1.0),
+    ("EUR", "€", 0.92),
+    ("GBP", "£", 0.79),
+    ("JPY", "¥", 150.0),
+    ("CAD", "CA$", 1.36),
+    ("AUD", "A$", 1.52),
+    ("CHF", "CHF ", 0.88),
+    ("CNY", "CN¥", 7.20),
+    ("INR", "₹", 83.0),
+];
+
+/// Converts USD costs for display. Internal totals always stay in USD.
+#[derive(Debug, Clone)]
+pub struct Currency {
+    pub code: String,
+    pub symbol: String,
+    pub rate: f64,
+}
+
+impl Default for Currency {
+    fn default() -> Self {
+        Self {
+            code: "USD".to_string(),
+            symbol: "$".to_string(),
+            rate: 1.0,
+        }
+    }
+}
+
+impl Currency {
+    pub fn new(code: &str, rate: Option<f64>) -> Result<Self> {
+        let code = code.trim().to_uppercase();
+        let bundled = BUNDLED_RATES.iter().find(|(c, _, _)| *c == code);
+
+        let rate = match (rate, bundled) {
+            (Some(rate), _) if rate > 0.0 => rate,
+            (Some(rate), _) => anyhow::bail!("Exchange rate must be positive, got {}", rate),
+            (None, Some((_, _, rate))) => *rate,
+            (None, None) => anyhow::bail!(
+                "No bundled exchange rate for {}; pass --rate <USD to {}>",
+                code,
+                code
+            ),
+        };
+        let symbol = bundled
+            .map(|(_, symbol, _)| symbol.to_string())
+            .unwrap_or_else(|| format!("{} ", code));
+
+        Ok(Self { code, symbol, rate })
+    }
+
+    pub fn is_usd(&self) -> bool {
+        self.code == "USD"
+    }
+
+    pub fn convert(&self, usd: f64) -> f64 {
+        usd * self.rate
+    }
+
+    /// Format a USD amount in this currency, e.g. `€1.23`
+    pub fn format(&self, usd: f64, decimals: usize) -> String {
+        format!("{}{:.*}", self.symbol, decimals, self.convert(usd))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usd_passes_amounts_through() {
+        let usd = Currency::new("usd", None).unwrap();
+        assert!(usd.is_usd());
+        assert_eq!(usd.convert(12.5), 12.5);
+        assert_eq!(usd.format(12.345, 2), "$12.35");
+        assert_eq!(Currency::default().format(3.0, 0), "$3");
+    }
+
+    #[test]
+    fn bundled_codes_use_their_rate_and_symbol() {
+        let eur = Currency::new(" eur ", None).unwrap();
+        assert_eq!(eur.code, "EUR");
+        assert!(!eur.is_usd());
+        assert_eq!(eur.format(10.0, 2), "€9.20");
+        assert_eq!(Currency::new("JPY", None).unwrap().format(2.0, 0), "¥300");
+    }
+
+    #[test]
+    fn rate_flag_beats_the_bundled_rate() {
+        assert_eq!(Currency::new("EUR", Some(0.5)).unwrap().format(10.0, 2), "€5.00");
+
+        // Codes without a bundled rate work once given one, shown by their code
+        assert_eq!(Currency::new("sek", Some(10.0)).unwrap().format(1.5, 2), "SEK 15.00");
+    }
+
+    #[test]
+    fn unknown_codes_and_bad_rates_are_rejected() {
+        assert!(Currency::new("SEK", None).is_err());
+        assert!(Currency::new("EUR", Some(0.0)).is_err());
+        assert!(Currency::new("EUR", Some(-1.0)).is_err());
+        assert!(Currency::new("EUR", Some(f64::NAN)).is_err());
+    }
+}
@@ -1,100 +1,548 @@
 use anyhow::{Result, Context};
-use chrono::{Datelike, DateTime, Utc};
+use chrono::{Datelike, DateTime, Local, Timelike, Utc};
 use glob::glob;
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
 use tracing::{debug, warn, info};
 
-use crate::models::{UsageEntry, DailyUsage, SessionUsage, MonthlyUsage, TokenUsage, UsageStats, PricingData};
+use crate::models::{CostBreakdown, UsageEntry, DailyUsage, DailyModelUsage, LocalHourUsage, SessionUsage, MonthlyUsage, HourlyUsage, ModelUsage, ModelPricing, PriceOverride, TokenUsage, UsageStats, PricingData};
+use crate::util::pseudonym;
+
+/// Single entries above this many tokens are almost certainly corrupt
+const DEFAULT_ENTRY_TOKEN_WARN_THRESHOLD: u64 = 5_000_000;
+
+/// Slack for `with_modified_since`, so clock skew or a write racing the glob
+/// can't drop a file that still has entries in the window
+const MODIFIED_SINCE_MARGIN: Duration = Duration::from_secs(60 * 60);
+
+/// How long to wait before re-reading the active transcript when its last
+/// line is still being written
+const ACTIVE_FILE_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// Entries stamped further ahead than this are treated as clock skew or bad
+/// data and dropped unless `allow_future` is set
+const FUTURE_TIMESTAMP_TOLERANCE_HOURS: i64 = 24;
+
+/// Loader settings shared by every command, taken from global CLI flags
+#[derive(Debug, Clone, Default)]
+pub struct LoadOptions {
+    /// Keep synthetic entries (API errors, interrupted turns) in the totals
+    pub include_synthetic: bool,
+    /// Keep entries that report no token usage at all
+    pub include_empty: bool,
+    /// Count every entry's full cache usage, skipping the resumed-session adjustment
+    pub no_dedupe_cache: bool,
+    /// Report each resumed-session chain as one session
+    pub merge_sessions: bool,
+    /// Print per-phase load timings and counts to stderr
+    pub profile: bool,
+    /// Rates replacing the bundled pricing for matching models
+    pub price_overrides: Vec<PriceOverride>,
+    /// Replace project paths, session ids and data directories with pseudonyms
+    pub anonymize: bool,
+    /// Keep entries timestamped more than a day in the future
+    pub allow_future: bool,
+}
+
+/// What tailing mode remembers about one transcript between loads
+#[derive(Default)]
+struct TailState {
+    /// Bytes consumed so far; always the end of a complete line
+    offset: u64,
+    /// Entries from the lines before `offset`
+    entries: Vec<UsageEntry>,
+}
+
+/// Where a discovery candidate came from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathOrigin {
+    /// An entry of `CLAUDE_CONFIG_DIR`
+    Env,
+    /// One of the built-in default locations
+    Default,
+}
+
+/// One directory [`DataLoader::probe_claude_paths`] looked at
+#[derive(Debug, Clone)]
+pub struct PathProbe {
+    pub origin: PathOrigin,
+    /// The directory as given or guessed, before resolving `projects`
+    pub path: PathBuf,
+    pub exists: bool,
+    /// Transcript folder this resolves to, usually `path/projects`
+    pub projects_dir: Option<PathBuf>,
+    /// Whether loads read from here; false for defaults when `CLAUDE_CONFIG_DIR`
+    /// resolved, and for duplicates of an earlier candidate
+    pub used: bool,
+}
 
 pub struct DataLoader {
-    claude_paths: Vec<PathBuf>,
+    /// Resolved `projects` directories to scan for transcripts
+    projects_dirs: Vec<PathBuf>,
+    /// Read transcript lines from stdin rather than `projects_dirs`
+    read_stdin: bool,
     pricing: PricingData,
+    entry_token_warn_threshold: u64,
+    options: LoadOptions,
+    /// Per-file state for incremental reloads; `None` re-reads every file each time
+    tail: Option<Mutex<HashMap<PathBuf, TailState>>>,
+    /// Skip transcripts last modified before this, minus `MODIFIED_SINCE_MARGIN`
+    modified_since: Option<SystemTime>,
+    /// Transcript Claude is still appending to, given a retry on a torn last line
+    active_file: Option<PathBuf>,
 }
 
 impl DataLoader {
-    pub fn new() -> Result<Self> {
-        let claude_paths = Self::find_claude_paths()?;
-        if claude_paths.is_empty() {
-            anyhow::bail!("No Claude data directories found");
+    pub fn new(options: &LoadOptions) -> Result<Self> {
+        let projects_dirs = Self::find_claude_paths()?;
+        if projects_dirs.is_empty() {
+            anyhow::bail!(
+                "No Claude data directories found in ~/.config/claude/projects, ~/.claude/projects \
+                 or the platform's app-data Claude folder. \
+                 Set CLAUDE_CONFIG_DIR to the directory holding your Claude Code transcripts, \
+                 or run `cc-monitor paths` to see what was checked."
+            );
         }
         
-        Ok(Self {
-            claude_paths,
-            pricing: PricingData::new(),
-        })
+        Ok(Self::with_projects_dirs(projects_dirs, false, options))
     }
     
+    /// Loader that skips directory discovery and reads JSONL from stdin instead
+    pub fn stdin(options: &LoadOptions) -> Self {
+        Self::with_projects_dirs(Vec::new(), true, options)
+    }
+    
+    /// Pick the stdin loader when a command's `--stdin` flag is set
+    pub fn for_input(read_stdin: bool, options: &LoadOptions) -> Result<Self> {
+        if read_stdin {
+            Ok(Self::stdin(options))
+        } else {
+            Self::new(options)
+        }
+    }
+    
+    /// Loader with no transcript source, for aggregating entries built in memory
+    #[cfg(test)]
+    pub fn offline(options: &LoadOptions) -> Self {
+        Self::with_projects_dirs(Vec::new(), false, options)
+    }
+    
+    /// Replace the bundled pricing table
+    #[cfg(test)]
+    pub fn with_pricing(mut self, pricing: PricingData) -> Self {
+        self.pricing = pricing;
+        self
+    }
+    
+    /// Remember each transcript's entries and read offset, so later loads only
+    /// parse lines appended since. Meant for watch modes that reload often.
+    pub fn with_tailing(mut self) -> Self {
+        self.tail = Some(Mutex::new(HashMap::new()));
+        self
+    }
+    
+    /// Only parse transcripts modified since `since`. A file can't hold entries
+    /// newer than its mtime, so this is safe for reports that only look at a
+    /// bounded recent window; all-time reports must not use it.
+    pub fn with_modified_since(mut self, since: DateTime<Utc>) -> Self {
+        self.modified_since = Some(SystemTime::from(since) - MODIFIED_SINCE_MARGIN);
+        self
+    }
+    
+    /// Mark `path` as the transcript being written right now. When its last
+    /// line doesn't parse yet, it's read again once after a short delay rather
+    /// than dropped; other files are never retried.
+    pub fn with_active_file(mut self, path: &Path) -> Self {
+        self.active_file = Some(std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()));
+        self
+    }
+    
+    fn with_projects_dirs(projects_dirs: Vec<PathBuf>, read_stdin: bool, options: &LoadOptions) -> Self {
+        // Allow overriding the sanity threshold for unusually large requests
+        let entry_token_warn_threshold = std::env::var("CC_MONITOR_MAX_ENTRY_TOKENS")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_ENTRY_TOKEN_WARN_THRESHOLD);
+        
+        let mut pricing = PricingData::new();
+        for price in &options.price_overrides {
+            pricing.set_override(price.clone());
+        }
+        
+        Self {
+            projects_dirs,
+            read_stdin,
+            pricing,
+            entry_token_warn_threshold,
+            options: options.clone(),
+            tail: None,
+            modified_since: None,
+            active_file: None,
+        }
+    }
+    
+    /// Find the `projects` directories holding Claude transcripts
     fn find_claude_paths() -> Result<Vec<PathBuf>> {
-        let mut paths = Vec::new();
+        let paths: Vec<_> = Self::probe_claude_paths()
+            .into_iter()
+            .filter(|probe| probe.used)
+            .filter_map(|probe| probe.projects_dir)
+            .collect();
+        
+        for path in &paths {
+            info!("Using Claude data directory: {:?}", path);
+        }
+        
+        Ok(paths)
+    }
+    
+    /// Every directory discovery considers, in order: each `CLAUDE_CONFIG_DIR`
+    /// entry, then the default locations, which are only used when no
+    /// `CLAUDE_CONFIG_DIR` entry resolved
+    pub fn probe_claude_paths() -> Vec<PathProbe> {
+        let mut probes = Vec::new();
         
         // Check environment variable first
         if let Ok(env_paths) = std::env::var("CLAUDE_CONFIG_DIR") {
             for path_str in env_paths.split(',') {
-                let path = PathBuf::from(path_str.trim());
-                if path.exists() {
-                    let projects_path = path.join("projects");
-                    if projects_path.exists() {
-                        paths.push(path);
-                    }
+                let trimmed = path_str.trim().trim_end_matches(['/', '\\']);
+                if trimmed.is_empty() {
+                    continue;
                 }
+                let projects_dir = Self::resolve_env_path(path_str);
+                if projects_dir.is_none() {
+                    debug!("No Claude data found under {:?}", trimmed);
+                }
+                probes.push(PathProbe {
+                    origin: PathOrigin::Env,
+                    path: PathBuf::from(trimmed),
+                    exists: Path::new(trimmed).is_dir(),
+                    used: projects_dir.is_some(),
+                    projects_dir,
+                });
             }
         }
         
         // If no env paths, check default locations
-        if paths.is_empty() {
-            if let Some(home) = directories::BaseDirs::new() {
+        let env_resolved = probes.iter().any(|probe| probe.used);
+        if let Some(home) = directories::BaseDirs::new() {
+            let candidates = [
                 // New location: ~/.config/claude
-                let config_path = home.config_dir().join("claude");
-                if config_path.join("projects").exists() {
-                    paths.push(config_path);
-                }
-                
+                home.config_dir().join("claude"),
                 // Old location: ~/.claude
-                let old_path = home.home_dir().join(".claude");
-                if old_path.join("projects").exists() {
-                    paths.push(old_path);
+                home.home_dir().join(".claude"),
+                // Per-OS app-data folders, e.g. %APPDATA%\Claude on Windows or
+                // ~/Library/Application Support/Claude on macOS
+                home.config_dir().join("Claude"),
+                home.data_dir().join("Claude"),
+            ];
+            for candidate in candidates {
+                let projects_dir = candidate.join("projects");
+                let found = projects_dir.exists();
+                if found && !env_resolved {
+                    debug!("Found Claude data directory candidate {:?}", projects_dir);
                 }
+                probes.push(PathProbe {
+                    origin: PathOrigin::Default,
+                    exists: candidate.exists(),
+                    used: found && !env_resolved,
+                    projects_dir: found.then_some(projects_dir),
+                    path: candidate,
+                });
             }
         }
         
-        Ok(paths)
+        // Several candidates can resolve to the same place (symlinks, case-insensitive
+        // filesystems, or config_dir == data_dir on Windows/macOS)
+        let mut seen = HashSet::new();
+        for probe in probes.iter_mut().filter(|probe| probe.used) {
+            if let Some(dir) = &probe.projects_dir {
+                probe.used = seen.insert(std::fs::canonicalize(dir).unwrap_or_else(|_| dir.clone()));
+            }
+        }
+        
+        probes
+    }
+    
+    /// Normalize a `CLAUDE_CONFIG_DIR` entry. Accepts the config dir itself,
+    /// its `projects` folder, or any folder that already holds transcripts.
+    fn resolve_env_path(path_str: &str) -> Option<PathBuf> {
+        let trimmed = path_str.trim().trim_end_matches(['/', '\\']);
+        if trimmed.is_empty() {
+            return None;
+        }
+        
+        let path = PathBuf::from(trimmed);
+        if !path.is_dir() {
+            return None;
+        }
+        
+        let projects_path = path.join("projects");
+        if projects_path.is_dir() {
+            return Some(projects_path);
+        }
+        
+        if path.file_name().is_some_and(|name| name == "projects") || Self::contains_jsonl(&path) {
+            return Some(path);
+        }
+        
+        None
+    }
+    
+    fn contains_jsonl(dir: &Path) -> bool {
+        dir.join("**/*.jsonl")
+            .to_str()
+            .and_then(|pattern| glob(pattern).ok())
+            .is_some_and(|mut matches| matches.next().is_some())
+    }
+    
+    /// Directories searched for transcripts, as shown to the user: their
+    /// source pseudonyms with `--anonymize`
+    pub fn data_dirs(&self) -> Vec<String> {
+        self.projects_dirs.iter()
+            .map(|dir| if self.options.anonymize {
+                pseudonym("source", &Self::source_label(dir))
+            } else {
+                dir.display().to_string()
+            })
+            .collect()
+    }
+    
+    /// Explain where we looked when directories exist but hold no usable entries
+    pub fn empty_state_message(&self) -> String {
+        if self.read_stdin {
+            return "No usage data found in the JSONL read from stdin.".to_string();
+        }
+        
+        let searched: Vec<_> = self.data_dirs().iter()
+            .map(|dir| format!("  {}", dir))
+            .collect();
+        format!(
+            "No usage data found. Searched:\n{}\n\
+             Set CLAUDE_CONFIG_DIR to the Claude directory holding your transcripts \
+             (comma-separated for several), or run `cc-monitor paths` for details.",
+            searched.join("\n")
+        )
     }
     
     pub fn load_all_usage(&self) -> Result<UsageStats> {
-        let mut all_entries = Vec::new();
+        if self.read_stdin {
+            return self.load_from_reader(io::stdin().lock());
+        }
+        let entries = self.load_entries()?;
+        
+        let started = Instant::now();
+        let stats = self.aggregate(entries)?;
+        if self.options.profile {
+            print_aggregate_profile(started.elapsed(), &stats);
+        }
+        Ok(stats)
+    }
+    
+    /// Every parsed transcript line, before filtering, dedupe or aggregation
+    pub fn load_entries(&self) -> Result<Vec<UsageEntry>> {
+        if self.read_stdin {
+            return self.load_entries_from_reader(io::stdin().lock());
+        }
         
-        for claude_path in &self.claude_paths {
-            let pattern = claude_path.join("projects").join("**/*.jsonl");
+        // Collect every path up front so globbing and parsing can be timed apart
+        let started = Instant::now();
+        let mut paths = Vec::new();
+        for projects_dir in &self.projects_dirs {
+            let root = Self::source_label(projects_dir);
+            let pattern = projects_dir.join("**/*.jsonl");
             let pattern_str = pattern.to_str()
                 .context("Invalid path")?;
             
             for entry in glob(pattern_str)? {
                 match entry {
-                    Ok(path) => {
-                        debug!("Loading file: {:?}", path);
-                        let entries = self.load_jsonl_file(&path)?;
-                        all_entries.extend(entries);
-                    }
+                    Ok(path) => paths.push((path, root.clone())),
                     Err(e) => warn!("Error reading path: {}", e),
                 }
             }
         }
         
-        self.aggregate_usage(all_entries)
+        // Unreadable metadata keeps the file, so the error surfaces when it's opened
+        let found_files = paths.len();
+        if let Some(cutoff) = self.modified_since {
+            paths.retain(|(path, _)| {
+                std::fs::metadata(path)
+                    .and_then(|meta| meta.modified())
+                    .map_or(true, |modified| modified >= cutoff)
+            });
+            debug!("Skipped {} transcript(s) not modified recently", found_files - paths.len());
+        }
+        let glob_time = started.elapsed();
+        
+        let started = Instant::now();
+        let mut all_entries = Vec::new();
+        let mut loaded_files = 0;
+        let mut skipped_files = 0;
+        for (path, root) in &paths {
+            debug!("Loading file: {:?}", path);
+            // One unreadable transcript shouldn't hide everything else
+            match self.load_jsonl_file(path, root) {
+                Ok(entries) => {
+                    all_entries.extend(entries);
+                    loaded_files += 1;
+                }
+                Err(e) => {
+                    warn!("Skipping {}: {}", self.transcript_label(path, root), e);
+                    skipped_files += 1;
+                }
+            }
+        }
+        let parse_time = started.elapsed();
+        
+        if skipped_files > 0 {
+            warn!("Skipped {} unreadable transcript file(s)", skipped_files);
+        }
+        info!("Loaded {} usage entries from {} transcript file(s)", all_entries.len(), loaded_files);
+        
+        if self.options.profile {
+            eprintln!(
+                "profile: glob       {:>10.2?}  {} file(s) found, {} skipped as not recently modified",
+                glob_time, found_files, found_files - paths.len()
+            );
+            eprintln!("profile: parse      {:>10.2?}  {} file(s), {} entries", parse_time, loaded_files, all_entries.len());
+        }
+        Ok(all_entries)
     }
     
-    fn load_jsonl_file(&self, path: &Path) -> Result<Vec<UsageEntry>> {
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-        let mut entries = Vec::new();
+    /// Aggregate transcript lines from an arbitrary reader, e.g. a single piped-in session
+    pub fn load_from_reader<R: BufRead>(&self, reader: R) -> Result<UsageStats> {
+        let entries = self.load_entries_from_reader(reader)?;
+        
+        let started = Instant::now();
+        let stats = self.aggregate(entries)?;
+        if self.options.profile {
+            print_aggregate_profile(started.elapsed(), &stats);
+        }
+        Ok(stats)
+    }
+    
+    fn load_entries_from_reader<R: BufRead>(&self, reader: R) -> Result<Vec<UsageEntry>> {
+        let started = Instant::now();
+        let entries = Self::parse_jsonl(reader, "stdin", None, None, None)?;
+        if self.options.profile {
+            eprintln!("profile: parse      {:>10.2?}  stdin, {} entries", started.elapsed(), entries.len());
+        }
+        Ok(entries)
+    }
+    
+    /// Rates used to price entries, including any `--price` overrides
+    pub fn pricing(&self) -> &PricingData {
+        &self.pricing
+    }
+    
+    /// Label for the data directory a transcript came from: the Claude config
+    /// dir when `projects_dir` is its `projects` folder, else the folder itself
+    fn source_label(projects_dir: &Path) -> String {
+        let root = match projects_dir.parent() {
+            Some(parent) if projects_dir.file_name().is_some_and(|name| name == "projects") => parent,
+            _ => projects_dir,
+        };
+        root.display().to_string()
+    }
+    
+    /// How a transcript is named in log messages: its path, or with
+    /// `--anonymize` the session and source pseudonyms its rows get
+    fn transcript_label(&self, path: &Path, source_root: &str) -> String {
+        if !self.options.anonymize {
+            return path.display().to_string();
+        }
+        let session_id = path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+        format!("{} in {}", pseudonym("session", &session_id), pseudonym("source", source_root))
+    }
+    
+    fn load_jsonl_file(&self, path: &Path, source_root: &str) -> Result<Vec<UsageEntry>> {
+        let mut file = File::open(path)?;
         
         // Extract session info from path: projects/{project}/{sessionId}.jsonl
         let session_id = path.file_stem()
             .and_then(|s| s.to_str())
             .map(|s| s.to_string());
+        let project = Self::decode_project_dir(path);
+        let source = self.transcript_label(path, source_root);
+        let source_root = Some(source_root.to_string());
+        
+        let retry_partial = self.is_active_file(path);
+        let Some(tail) = &self.tail else {
+            if retry_partial {
+                let bytes = Self::read_from(&mut file, 0, true)?;
+                return Ok(Self::parse_jsonl(&bytes[..], &source, session_id, project, source_root)?);
+            }
+            return Ok(Self::parse_jsonl(BufReader::new(file), &source, session_id, project, source_root)?);
+        };
+        
+        let mut tail = tail.lock().unwrap_or_else(PoisonError::into_inner);
+        let state = tail.entry(path.to_path_buf()).or_default();
+        if file.metadata()?.len() < state.offset {
+            debug!("{:?} shrank since the last load; re-reading it", path);
+            *state = TailState::default();
+        }
+        
+        let appended = Self::read_from(&mut file, state.offset, retry_partial)?;
+        
+        // Only complete lines advance the offset. A trailing partial line is
+        // parsed for this load but read again next time, once it's finished.
+        let complete = appended.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+        let (lines, rest) = appended.split_at(complete);
+        state.entries.extend(Self::parse_jsonl(lines, &source, session_id.clone(), project.clone(), source_root.clone())?);
+        state.offset += complete as u64;
+        
+        let mut entries = state.entries.clone();
+        entries.extend(Self::parse_jsonl(rest, &source, session_id, project, source_root)?);
+        Ok(entries)
+    }
+    
+    fn is_active_file(&self, path: &Path) -> bool {
+        let Some(active) = &self.active_file else {
+            return false;
+        };
+        // Only canonicalize files that could be it
+        path == active
+            || (path.file_name() == active.file_name()
+                && std::fs::canonicalize(path).is_ok_and(|path| &path == active))
+    }
+    
+    /// Read `file` from `offset` to its end. With `retry_partial`, a last line
+    /// that isn't valid JSON yet gets one short wait to be finished.
+    fn read_from(file: &mut File, offset: u64, retry_partial: bool) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_to_end(&mut bytes)?;
+        
+        if retry_partial && Self::ends_mid_line(&bytes) {
+            debug!("Last line is still being written; reading again in {:?}", ACTIVE_FILE_RETRY_DELAY);
+            std::thread::sleep(ACTIVE_FILE_RETRY_DELAY);
+            bytes.clear();
+            file.seek(SeekFrom::Start(offset))?;
+            file.read_to_end(&mut bytes)?;
+        }
+        Ok(bytes)
+    }
+    
+    /// Whether the text after the last newline is a line that doesn't parse yet
+    fn ends_mid_line(bytes: &[u8]) -> bool {
+        let start = bytes.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+        let last = bytes[start..].trim_ascii();
+        !last.is_empty() && serde_json::from_slice::<serde::de::IgnoredAny>(last).is_err()
+    }
+    
+    /// Parse usage entries line by line; `source` only labels log messages
+    fn parse_jsonl<R: BufRead>(
+        reader: R,
+        source: &str,
+        session_id: Option<String>,
+        project: Option<String>,
+        source_root: Option<String>,
+    ) -> io::Result<Vec<UsageEntry>> {
+        let mut entries = Vec::new();
         
         for (line_num, line) in reader.lines().enumerate() {
             match line {
@@ -107,44 +555,119 @@ impl DataLoader {
                     // Validate that the line is complete JSON (starts with { and ends with })
                     let trimmed = json_str.trim();
                     if !trimmed.starts_with('{') || !trimmed.ends_with('}') {
-                        debug!("Skipping incomplete JSON line {} in {:?}", line_num + 1, path);
+                        debug!("Skipping incomplete JSON line {} in {}", line_num + 1, source);
                         continue;
                     }
                     
                     match serde_json::from_str::<UsageEntry>(&json_str) {
                         Ok(mut entry) => {
-                            // Fill in session_id if missing
+                            // Fill in session_id and project if missing
                             if entry.session_id.is_none() {
                                 entry.session_id = session_id.clone();
                             }
+                            if entry.cwd.is_none() {
+                                entry.cwd = project.clone();
+                            }
+                            entry.source_root = source_root.clone();
                             entries.push(entry);
                         }
                         Err(e) => {
-                            debug!("Failed to parse line {} in {:?}: {}", line_num + 1, path, e);
+                            debug!("Failed to parse line {} in {}: {}", line_num + 1, source, e);
                         }
                     }
                 }
-                Err(e) => {
-                    warn!("Error reading line {} in {:?}: {}", line_num + 1, path, e);
+                // Invalid UTF-8 only spoils this line; any other error would repeat forever
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                    warn!("Error reading line {} in {}: {}", line_num + 1, source, e);
                 }
+                Err(e) => return Err(e),
             }
         }
         
         Ok(entries)
     }
     
-    fn aggregate_usage(&self, mut entries: Vec<UsageEntry>) -> Result<UsageStats> {
+    /// Recover the project path from the slug-encoded directory name Claude
+    /// uses under `projects/`, e.g. `-home-me-dev-foo` -> `/home/me/dev/foo`.
+    /// The encoding is lossy, so dashes inside directory names become slashes.
+    fn decode_project_dir(path: &Path) -> Option<String> {
+        let dir_name = path.parent()?.file_name()?.to_str()?;
+        if dir_name == "projects" {
+            return None;
+        }
+        
+        if dir_name.starts_with('-') {
+            Some(dir_name.replace('-', "/"))
+        } else {
+            Some(dir_name.to_string())
+        }
+    }
+    
+    /// Build stats from already-parsed entries, without touching the filesystem
+    pub fn aggregate(&self, mut entries: Vec<UsageEntry>) -> Result<UsageStats> {
+        // Synthetic placeholders would otherwise open empty day/session buckets
+        if !self.options.include_synthetic {
+            let before = entries.len();
+            entries.retain(|e| !e.is_synthetic());
+            if entries.len() < before {
+                debug!("Skipped {} synthetic entries", before - entries.len());
+            }
+        }
+        
+        // Likewise for lines that carry a model but bill nothing, so days and
+        // sessions only reflect real activity
+        if !self.options.include_empty {
+            let before = entries.len();
+            entries.retain(|e| e.message.usage.total() > 0);
+            if entries.len() < before {
+                debug!("Skipped {} entries with no token usage", before - entries.len());
+            }
+        }
+        
+        // A far-future line would count towards "today", the current block and
+        // the burn rate on every run until its date came round
+        if !self.options.allow_future {
+            let cutoff = Utc::now() + chrono::Duration::hours(FUTURE_TIMESTAMP_TOLERANCE_HOURS);
+            let before = entries.len();
+            entries.retain(|e| e.timestamp <= cutoff);
+            if entries.len() < before {
+                warn!(
+                    "Skipped {} entries timestamped more than {}h in the future (pass --allow-future to keep them)",
+                    before - entries.len(), FUTURE_TIMESTAMP_TOLERANCE_HOURS
+                );
+            }
+        }
+        
         // Sort entries by timestamp to ensure consistent processing order
         entries.sort_by_key(|e| e.timestamp);
         
         let mut daily_map: BTreeMap<chrono::NaiveDate, DailyUsage> = BTreeMap::new();
         let mut session_map: BTreeMap<String, SessionUsage> = BTreeMap::new();
         let mut monthly_map: BTreeMap<String, MonthlyUsage> = BTreeMap::new();
+        let mut hourly_map: BTreeMap<i64, HourlyUsage> = BTreeMap::new();
+        let mut model_map: BTreeMap<String, ModelUsage> = BTreeMap::new();
+        let mut daily_model_map: BTreeMap<(chrono::NaiveDate, String), DailyModelUsage> = BTreeMap::new();
+        let mut local_hour_map: BTreeMap<(chrono::NaiveDate, u32), LocalHourUsage> = BTreeMap::new();
         let mut total_tokens = TokenUsage::default();
         let mut total_cost = 0.0;
+        let mut total_breakdown = CostBreakdown::default();
         
         // Detect resumed sessions to avoid double-counting cache tokens
-        let session_chains = self.detect_resumed_sessions(&entries);
+        let session_chains = if self.options.no_dedupe_cache && !self.options.merge_sessions {
+            Vec::new()
+        } else {
+            self.detect_resumed_sessions(&entries)
+        };
+        let dedupe_chains: &[Vec<String>] = if self.options.no_dedupe_cache { &[] } else { &session_chains };
+        
+        // With --merge-sessions, later sessions in a chain report under its first one
+        let merged_ids: HashMap<&str, &str> = if self.options.merge_sessions {
+            session_chains.iter()
+                .flat_map(|chain| chain[1..].iter().map(move |id| (id.as_str(), chain[0].as_str())))
+                .collect()
+        } else {
+            HashMap::new()
+        };
         
         // Track maximum cache seen per session chain
         let mut chain_cache_max: BTreeMap<usize, (u64, u64)> = BTreeMap::new();
@@ -153,13 +676,25 @@ impl DataLoader {
             let date = entry.timestamp.date_naive();
             let month = format!("{:04}-{:02}", date.year(), date.month());
             let session_id = entry.session_id.clone().unwrap_or_else(|| "unknown".to_string());
+            let model = entry.message.model.clone();
+            
+            // Flag obviously-bad lines rather than silently trusting them
+            let entry_total = entry.message.usage.total();
+            if entry_total > self.entry_token_warn_threshold {
+                warn!(
+                    "Entry in session {} at {} has suspicious token count {} (threshold {})",
+                    session_id, entry.timestamp, entry_total, self.entry_token_warn_threshold
+                );
+            }
             
-            // Find which session chain this belongs to
-            let chain_idx = session_chains.iter()
-                .position(|chain| chain.contains(&session_id));
+            // Find which resumed chain this belongs to. A session on its own repeats
+            // no one else's cache, so its entries are counted as logged.
+            let chain_idx = dedupe_chains.iter()
+                .position(|chain| chain.len() > 1 && chain.contains(&session_id));
             
             // Adjust usage for resumed sessions to avoid double-counting cache
             let mut adjusted_usage = entry.message.usage.clone();
+            let mut cache_adjusted = false;
             
             if let Some(idx) = chain_idx {
                 let (max_cache_read, max_cache_creation) = chain_cache_max.entry(idx)
@@ -171,6 +706,9 @@ impl DataLoader {
                 let incremental_cache_creation = adjusted_usage.cache_creation_input_tokens
                     .saturating_sub(*max_cache_creation);
                 
+                cache_adjusted = incremental_cache_read != adjusted_usage.cache_read_input_tokens
+                    || incremental_cache_creation != adjusted_usage.cache_creation_input_tokens;
+                
                 adjusted_usage.cache_read_input_tokens = incremental_cache_read;
                 adjusted_usage.cache_creation_input_tokens = incremental_cache_creation;
                 
@@ -179,64 +717,144 @@ impl DataLoader {
                 *max_cache_creation = (*max_cache_creation).max(entry.message.usage.cache_creation_input_tokens);
             }
             
-            // Calculate cost with adjusted usage
-            let cost = if let Some(cost) = entry.message.cost_usd {
-                cost
-            } else {
-                self.pricing.calculate_cost(&entry.message.model, &adjusted_usage)
+            // The long-context tier depends on this request's real input size, so
+            // decide it before the resumed-session cache adjustment shrinks it
+            let long_context = ModelPricing::is_long_context(&entry.message.usage);
+            
+            // Calculate cost with adjusted usage. A logged costUSD reflects the full,
+            // unadjusted cache usage, so it's only trusted when no adjustment was made,
+            // and never over rates given with --price.
+            let cost = match entry.message.cost_usd {
+                Some(cost) if !cache_adjusted && !self.pricing.is_overridden(&model) => cost,
+                _ => self.pricing.calculate_cost(&model, &adjusted_usage, long_context),
             };
             
+            let breakdown = self.pricing.cost_breakdown(&model, &adjusted_usage, long_context);
+            
             // Update totals with adjusted usage
             total_tokens.add(&adjusted_usage);
             total_cost += cost;
+            total_breakdown.add(&breakdown);
             
             // Update daily stats
             let daily = daily_map.entry(date).or_insert_with(|| DailyUsage {
                 date,
                 tokens: TokenUsage::default(),
                 total_cost: 0.0,
+                cost_breakdown: CostBreakdown::default(),
                 models_used: HashSet::new(),
                 session_count: 0,
             });
             daily.tokens.add(&adjusted_usage);
             daily.total_cost += cost;
-            daily.models_used.insert(entry.message.model.clone());
+            daily.cost_breakdown.add(&breakdown);
+            daily.models_used.insert(model.clone());
+            
+            let daily_model = daily_model_map.entry((date, model.clone())).or_insert_with(|| DailyModelUsage {
+                date,
+                model: model.clone(),
+                tokens: TokenUsage::default(),
+                total_cost: 0.0,
+            });
+            daily_model.tokens.add(&adjusted_usage);
+            daily_model.total_cost += cost;
             
             // Update session stats
-            let session = session_map.entry(session_id.clone()).or_insert_with(|| SessionUsage {
-                session_id: session_id.clone(),
+            let session_key = merged_ids.get(session_id.as_str()).map_or_else(|| session_id.clone(), |id| id.to_string());
+            let session = session_map.entry(session_key.clone()).or_insert_with(|| SessionUsage {
+                session_id: session_key,
                 project_path: entry.cwd.clone().unwrap_or_else(|| "unknown".to_string()),
+                source_root: entry.source_root.clone(),
                 tokens: TokenUsage::default(),
                 total_cost: 0.0,
+                first_activity: entry.timestamp,
                 last_activity: entry.timestamp,
                 models_used: HashSet::new(),
             });
             session.tokens.add(&adjusted_usage);
             session.total_cost += cost;
+            session.first_activity = session.first_activity.min(entry.timestamp);
             session.last_activity = session.last_activity.max(entry.timestamp);
-            session.models_used.insert(entry.message.model.clone());
+            session.models_used.insert(model.clone());
+            
+            // Update per-model stats
+            let model_usage = model_map.entry(model.clone()).or_insert_with(|| ModelUsage {
+                model: model.clone(),
+                tokens: TokenUsage::default(),
+                total_cost: 0.0,
+                first_seen: entry.timestamp,
+                last_seen: entry.timestamp,
+                session_count: 0,
+            });
+            model_usage.tokens.add(&adjusted_usage);
+            model_usage.total_cost += cost;
+            model_usage.first_seen = model_usage.first_seen.min(entry.timestamp);
+            model_usage.last_seen = model_usage.last_seen.max(entry.timestamp);
             
             // Update monthly stats
             let monthly = monthly_map.entry(month.clone()).or_insert_with(|| MonthlyUsage {
                 month: month.clone(),
                 tokens: TokenUsage::default(),
                 total_cost: 0.0,
+                cost_breakdown: CostBreakdown::default(),
                 models_used: HashSet::new(),
                 daily_breakdown: Vec::new(),
             });
             monthly.tokens.add(&adjusted_usage);
             monthly.total_cost += cost;
-            monthly.models_used.insert(entry.message.model);
+            monthly.cost_breakdown.add(&breakdown);
+            monthly.models_used.insert(model.clone());
+            
+            // Update hourly stats, keyed by hours since epoch
+            let hour_key = entry.timestamp.timestamp().div_euclid(3600);
+            let hourly = hourly_map.entry(hour_key).or_insert_with(|| HourlyUsage {
+                hour: DateTime::<Utc>::from_timestamp(hour_key * 3600, 0).unwrap_or(entry.timestamp),
+                tokens: TokenUsage::default(),
+                total_cost: 0.0,
+            });
+            hourly.tokens.add(&adjusted_usage);
+            hourly.total_cost += cost;
+            
+            let local = entry.timestamp.with_timezone(&Local);
+            let local_key = (local.date_naive(), local.hour());
+            let local_hour = local_hour_map.entry(local_key).or_insert_with(|| LocalHourUsage {
+                date: local_key.0,
+                hour: local_key.1,
+                tokens: TokenUsage::default(),
+                total_cost: 0.0,
+            });
+            local_hour.tokens.add(&adjusted_usage);
+            local_hour.total_cost += cost;
         }
         
         // Convert maps to sorted vectors
-        let mut daily: Vec<_> = daily_map.into_iter().map(|(_, v)| v).collect();
+        let mut daily: Vec<_> = daily_map.into_values().collect();
         daily.sort_by_key(|d| d.date);
         
-        let mut sessions: Vec<_> = session_map.into_iter().map(|(_, v)| v).collect();
+        let mut sessions: Vec<_> = session_map.into_values().collect();
         sessions.sort_by_key(|s| std::cmp::Reverse(s.last_activity));
         
-        let mut monthly: Vec<_> = monthly_map.into_iter().map(|(_, v)| v).collect();
+        // Sessions are the only place paths and ids end up, so every report,
+        // export and the dashboard see the pseudonyms
+        if self.options.anonymize {
+            for session in &mut sessions {
+                session.session_id = pseudonym("session", &session.session_id);
+                session.project_path = pseudonym("project", &session.project_path);
+                session.source_root = session.source_root.as_deref().map(|root| pseudonym("source", root));
+            }
+        }
+        
+        let hourly: Vec<_> = hourly_map.into_values().collect();
+        
+        let mut models: Vec<_> = model_map.into_values().collect();
+        for model in &mut models {
+            model.session_count = sessions.iter()
+                .filter(|s| s.models_used.contains(&model.model))
+                .count();
+        }
+        models.sort_by(|a, b| b.total_cost.total_cmp(&a.total_cost));
+        
+        let mut monthly: Vec<_> = monthly_map.into_values().collect();
         monthly.sort_by_key(|m| m.month.clone());
         
         // Add daily breakdown to monthly stats
@@ -254,15 +872,22 @@ impl DataLoader {
         Ok(UsageStats {
             total_tokens,
             total_cost,
+            cost_breakdown: total_breakdown,
             sessions,
             daily,
             monthly,
+            hourly,
+            models,
+            // Keyed by (date, model), so already in order
+            daily_models: daily_model_map.into_values().collect(),
+            local_hours: local_hour_map.into_values().collect(),
         })
     }
     
     /// Detect resumed sessions based on timing and project
-    /// Sessions that start within 10 minutes of each other in the same project
-    /// are likely resumed sessions sharing the same cache
+    /// Sessions in the same project that start within 10 minutes of the previous
+    /// one ending are likely resumed sessions sharing the same cache. Each chain
+    /// lists its sessions in start order.
     fn detect_resumed_sessions(&self, entries: &[UsageEntry]) -> Vec<Vec<String>> {
         // Build session info
         let mut session_times: BTreeMap<String, (DateTime<Utc>, DateTime<Utc>, String)> = BTreeMap::new();
@@ -277,39 +902,332 @@ impl DataLoader {
             }
         }
         
-        // Group sessions into chains
+        // Walk sessions in start order so each one can extend the chain before
+        // it, measured from where that chain's latest session ended
+        let mut sessions: Vec<_> = session_times.into_iter().collect();
+        sessions.sort_by_key(|(_, (start, _, _))| *start);
+        
         let mut chains: Vec<Vec<String>> = Vec::new();
-        let mut processed = HashSet::new();
+        // Per project: index into `chains` of the latest chain, and when it ends
+        let mut latest: HashMap<String, (usize, DateTime<Utc>)> = HashMap::new();
         
-        for (session_id, (start, end, project)) in &session_times {
-            if processed.contains(session_id) {
-                continue;
-            }
-            
-            let mut chain = vec![session_id.clone()];
-            processed.insert(session_id.clone());
-            
-            // Find sessions that might be resumptions
-            for (other_id, (other_start, _, other_project)) in &session_times {
-                if processed.contains(other_id) || other_project != project {
-                    continue;
+        for (session_id, (start, end, project)) in sessions {
+            match latest.get_mut(&project) {
+                // Check if this session starts shortly after the chain ends
+                Some((idx, chain_end)) if (0..=10).contains(&start.signed_duration_since(*chain_end).num_minutes()) => {
+                    chains[*idx].push(session_id);
+                    *chain_end = (*chain_end).max(end);
                 }
-                
-                // Check if this session starts shortly after the current chain ends
-                let gap_minutes = other_start.signed_duration_since(*end).num_minutes();
-                if gap_minutes >= 0 && gap_minutes <= 10 {
-                    chain.push(other_id.clone());
-                    processed.insert(other_id.clone());
+                _ => {
+                    latest.insert(project, (chains.len(), end));
+                    chains.push(vec![session_id]);
                 }
             }
-            
-            if chain.len() > 1 {
-                info!("Detected resumed session chain with {} sessions", chain.len());
-            }
-            
-            chains.push(chain);
+        }
+        
+        for chain in chains.iter().filter(|chain| chain.len() > 1) {
+            info!("Detected resumed session chain with {} sessions", chain.len());
         }
         
         chains
     }
-}
\ No newline at end of file
+}
+
+/// `--profile` line for the aggregation phase
+fn print_aggregate_profile(elapsed: Duration, stats: &UsageStats) {
+    eprintln!(
+        "profile: aggregate  {:>10.2?}  {} session(s), {} day(s)",
+        elapsed, stats.sessions.len(), stats.daily.len()
+    );
+}
+
+/// Fixtures for tests that aggregate entries built in memory
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::*;
+    use crate::models::Message;
+    use chrono::TimeZone;
+
+    pub(crate) const SONNET: &str = "claude-sonnet-4-20250514";
+
+    /// 2025-06-`day` at `hour`:`minute` UTC
+    pub(crate) fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, day, hour, minute, 0).unwrap()
+    }
+
+    pub(crate) fn usage(input: u64, output: u64, cache_creation: u64, cache_read: u64) -> TokenUsage {
+        TokenUsage {
+            input_tokens: input,
+            output_tokens: output,
+            cache_creation_input_tokens: cache_creation,
+            cache_read_input_tokens: cache_read,
+        }
+    }
+
+    /// A Sonnet 4 request in `/home/me/project` with no logged cost
+    pub(crate) fn entry(session: &str, timestamp: DateTime<Utc>, usage: TokenUsage) -> UsageEntry {
+        UsageEntry {
+            timestamp,
+            session_id: Some(session.to_string()),
+            version: None,
+            cwd: Some("/home/me/project".to_string()),
+            message: Message {
+                model: SONNET.to_string(),
+                usage,
+                request_id: None,
+                message_id: None,
+                cost_usd: None,
+            },
+            is_api_error_message: false,
+            source_root: None,
+        }
+    }
+
+    pub(crate) fn aggregate(options: &LoadOptions, entries: Vec<UsageEntry>) -> UsageStats {
+        DataLoader::offline(options).aggregate(entries).unwrap()
+    }
+
+    pub(crate) fn assert_cost(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "cost {} != {}", actual, expected);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::*;
+    use super::*;
+
+    #[test]
+    fn aggregates_entries_into_days_and_sessions() {
+        let stats = aggregate(&LoadOptions::default(), vec![
+            entry("a", at(1, 9, 0), usage(1_000, 500, 0, 0)),
+            entry("a", at(1, 9, 5), usage(2_000, 500, 0, 0)),
+            entry("b", at(2, 14, 0), usage(100_000, 0, 0, 0)),
+        ]);
+
+        assert_eq!(stats.daily.len(), 2);
+        assert_eq!(stats.sessions.len(), 2);
+        assert_eq!(stats.total_tokens.input_tokens, 103_000);
+        assert_eq!(stats.total_tokens.output_tokens, 1_000);
+        // $3/Mtok input, $15/Mtok output
+        assert_cost(stats.total_cost, 103_000.0 * 3e-6 + 1_000.0 * 15e-6);
+    }
+
+    fn with_cost(mut entry: UsageEntry, cost: f64) -> UsageEntry {
+        entry.message.cost_usd = Some(cost);
+        entry
+    }
+
+    #[test]
+    fn logged_cost_is_kept_outside_resumed_chains() {
+        // One session re-reading its own cache on every request
+        let stats = aggregate(&LoadOptions::default(), vec![
+            with_cost(entry("a", at(1, 9, 0), usage(100, 100, 0, 50_000)), 0.5),
+            with_cost(entry("a", at(1, 9, 1), usage(100, 100, 0, 50_000)), 0.5),
+            with_cost(entry("a", at(1, 9, 2), usage(100, 100, 0, 60_000)), 0.5),
+        ]);
+
+        assert_cost(stats.total_cost, 1.5);
+        assert_eq!(stats.total_tokens.cache_read_input_tokens, 160_000);
+    }
+
+    #[test]
+    fn resumed_chain_reprices_entries_with_repeated_cache() {
+        let stats = aggregate(&LoadOptions::default(), vec![
+            with_cost(entry("a", at(1, 9, 0), usage(100, 100, 0, 50_000)), 0.5),
+            // Resumed 5 minutes later, replaying a's cache plus 10k more
+            with_cost(entry("b", at(1, 9, 5), usage(100, 100, 0, 60_000)), 0.5),
+        ]);
+
+        // a is the chain's first request, so its logged cost stands; b is
+        // re-priced on its 10k of new cache reads
+        let b_cost = 100.0 * 3e-6 + 100.0 * 15e-6 + 10_000.0 * 0.3e-6;
+        assert_cost(stats.total_cost, 0.5 + b_cost);
+        assert_eq!(stats.total_tokens.cache_read_input_tokens, 60_000);
+    }
+
+    fn synthetic(mut entry: UsageEntry) -> UsageEntry {
+        entry.message.model = "<synthetic>".to_string();
+        entry
+    }
+
+    #[test]
+    fn synthetic_entries_are_excluded_by_default() {
+        let entries = vec![
+            entry("a", at(1, 9, 0), usage(1_000, 500, 0, 0)),
+            synthetic(entry("b", at(1, 9, 5), usage(10, 20, 0, 0))),
+        ];
+
+        let stats = aggregate(&LoadOptions::default(), entries.clone());
+        assert_eq!(stats.sessions.len(), 1);
+        assert_eq!(stats.total_tokens.input_tokens, 1_000);
+        assert!(!stats.models.iter().any(|m| m.model == "<synthetic>"));
+
+        let options = LoadOptions { include_synthetic: true, ..LoadOptions::default() };
+        let stats = aggregate(&options, entries);
+        assert_eq!(stats.sessions.len(), 2);
+        assert_eq!(stats.total_tokens.input_tokens, 1_010);
+        assert!(stats.models.iter().any(|m| m.model == "<synthetic>"));
+    }
+
+    #[test]
+    fn zero_usage_entries_open_no_day_or_session_by_default() {
+        let entries = vec![
+            entry("a", at(1, 9, 0), usage(1_000, 500, 0, 0)),
+            entry("b", at(2, 9, 0), usage(0, 0, 0, 0)),
+        ];
+
+        let stats = aggregate(&LoadOptions::default(), entries.clone());
+        assert_eq!(stats.daily.len(), 1);
+        assert_eq!(stats.sessions.len(), 1);
+        assert_eq!(stats.sessions[0].session_id, "a");
+
+        let options = LoadOptions { include_empty: true, ..LoadOptions::default() };
+        let stats = aggregate(&options, entries);
+        assert_eq!(stats.daily.len(), 2);
+        assert_eq!(stats.sessions.len(), 2);
+        assert_eq!(stats.total_tokens.input_tokens, 1_000);
+    }
+
+    #[test]
+    fn far_future_entries_are_skipped_by_default() {
+        let now = Utc::now();
+        let entries = vec![
+            entry("a", now, usage(1_000, 500, 0, 0)),
+            entry("a", now + chrono::Duration::days(30), usage(50_000, 50_000, 0, 0)),
+        ];
+
+        let stats = aggregate(&LoadOptions::default(), entries.clone());
+        assert_eq!(stats.daily.len(), 1);
+        assert_eq!(stats.daily[0].date, now.date_naive());
+        assert_eq!(stats.daily[0].tokens.input_tokens, 1_000);
+        assert_eq!(stats.sessions[0].last_activity, now);
+        assert_eq!(stats.total_tokens.output_tokens, 500);
+
+        let options = LoadOptions { allow_future: true, ..LoadOptions::default() };
+        let stats = aggregate(&options, entries);
+        assert_eq!(stats.daily.len(), 2);
+        assert_eq!(stats.total_tokens.output_tokens, 50_500);
+    }
+
+    #[test]
+    fn requests_over_200k_input_use_the_long_context_rates() {
+        let stats = aggregate(&LoadOptions::default(), vec![
+            entry("a", at(1, 9, 0), usage(250_000, 1_000, 0, 0)),
+        ]);
+
+        // $6/Mtok input, $22.50/Mtok output above 200k
+        assert_cost(stats.total_cost, 250_000.0 * 6e-6 + 1_000.0 * 22.5e-6);
+    }
+
+    #[test]
+    fn exactly_200k_input_uses_the_standard_rates() {
+        let stats = aggregate(&LoadOptions::default(), vec![
+            entry("a", at(1, 9, 0), usage(150_000, 1_000, 20_000, 30_000)),
+        ]);
+
+        assert_cost(
+            stats.total_cost,
+            150_000.0 * 3e-6 + 1_000.0 * 15e-6 + 20_000.0 * 3.75e-6 + 30_000.0 * 0.3e-6,
+        );
+    }
+
+    #[test]
+    fn cache_adjustment_keeps_the_long_context_tier() {
+        let stats = aggregate(&LoadOptions::default(), vec![
+            entry("a", at(1, 9, 0), usage(20_000, 1_000, 0, 190_000)),
+            // Resumed: the replayed 190k is dropped, leaving 20k of input, but
+            // the request itself still sent 210k
+            entry("b", at(1, 9, 5), usage(20_000, 1_000, 0, 190_000)),
+        ]);
+
+        let a_cost = 20_000.0 * 6e-6 + 1_000.0 * 22.5e-6 + 190_000.0 * 0.6e-6;
+        let b_cost = 20_000.0 * 6e-6 + 1_000.0 * 22.5e-6;
+        assert_eq!(stats.total_tokens.cache_read_input_tokens, 190_000);
+        assert_cost(stats.total_cost, a_cost + b_cost);
+    }
+
+    /// Three sessions, each resumed within 10 minutes of the previous one
+    /// ending, replaying the cache before it plus 10k more
+    fn three_session_chain() -> Vec<UsageEntry> {
+        vec![
+            entry("a", at(1, 9, 0), usage(100, 100, 0, 50_000)),
+            entry("a", at(1, 9, 10), usage(100, 100, 0, 50_000)),
+            entry("b", at(1, 9, 15), usage(100, 100, 0, 60_000)),
+            entry("b", at(1, 9, 30), usage(100, 100, 0, 60_000)),
+            entry("c", at(1, 9, 35), usage(100, 100, 0, 70_000)),
+        ]
+    }
+
+    #[test]
+    fn cache_is_deduplicated_across_a_whole_chain() {
+        let stats = aggregate(&LoadOptions::default(), three_session_chain());
+
+        // c starts more than 10 minutes after a ends, so it's only linked
+        // through b; every replayed read past the first is dropped
+        assert_eq!(stats.sessions.len(), 3);
+        assert_eq!(stats.total_tokens.input_tokens, 500);
+        assert_eq!(stats.total_tokens.cache_read_input_tokens, 70_000);
+        assert_cost(stats.total_cost, 500.0 * 3e-6 + 500.0 * 15e-6 + 70_000.0 * 0.3e-6);
+    }
+
+    #[test]
+    fn merge_sessions_reports_a_chain_as_one_row() {
+        let options = LoadOptions { merge_sessions: true, ..LoadOptions::default() };
+        let stats = aggregate(&options, three_session_chain());
+
+        assert_eq!(stats.sessions.len(), 1);
+        let session = &stats.sessions[0];
+        assert_eq!(session.session_id, "a");
+        assert_eq!(session.first_activity, at(1, 9, 0));
+        assert_eq!(session.last_activity, at(1, 9, 35));
+        assert_eq!(session.tokens.output_tokens, 500);
+        assert_eq!(session.tokens.cache_read_input_tokens, 70_000);
+    }
+
+    #[test]
+    fn no_dedupe_cache_counts_every_entry_as_logged() {
+        let options = LoadOptions { no_dedupe_cache: true, ..LoadOptions::default() };
+        let stats = aggregate(&options, three_session_chain());
+
+        assert_eq!(stats.sessions.len(), 3);
+        assert_eq!(stats.total_tokens.cache_read_input_tokens, 290_000);
+    }
+
+    #[test]
+    fn between_rebuilds_models_from_days_in_range() {
+        let mut opus = entry("b", at(2, 12, 0), usage(1_000, 0, 0, 0));
+        opus.message.model = "claude-opus-4-20250514".to_string();
+        let stats = aggregate(&LoadOptions::default(), vec![
+            entry("a", at(1, 23, 0), usage(100_000, 0, 0, 0)),
+            entry("a", at(2, 0, 30), usage(2_000, 0, 0, 0)),
+            opus,
+        ]);
+
+        let day = at(2, 0, 0).date_naive();
+        let range = stats.between(day, day);
+        assert_eq!(range.total_tokens.input_tokens, 3_000);
+
+        let sonnet = range.models.iter().find(|m| m.model == SONNET).unwrap();
+        assert_eq!(sonnet.tokens.input_tokens, 2_000);
+        assert_cost(sonnet.total_cost, 2_000.0 * 3e-6);
+        assert_eq!(sonnet.session_count, 1);
+        assert_eq!(range.models.len(), 2);
+        // Costliest first: opus at $0.015 ahead of sonnet at $0.006
+        assert_eq!(range.models[0].model, "claude-opus-4-20250514");
+
+        // Session a spans the range boundary and keeps its whole total
+        let session = range.sessions.iter().find(|s| s.session_id == "a").unwrap();
+        assert_eq!(session.tokens.input_tokens, 102_000);
+    }
+
+    #[test]
+    fn with_pricing_replaces_the_bundled_rates() {
+        let mut pricing = PricingData::new();
+        pricing.set_override("claude-sonnet-4=1,2".parse().unwrap());
+        let loader = DataLoader::offline(&LoadOptions::default()).with_pricing(pricing);
+
+        let stats = loader.aggregate(vec![entry("a", at(1, 9, 0), usage(1_000_000, 1_000_000, 0, 0))]).unwrap();
+        assert_cost(stats.total_cost, 3.0);
+    }
+}
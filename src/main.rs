@@ -1,42 +1,147 @@
 mod cli;
 mod commands;
+mod currency;
 mod data_loader;
 mod models;
+mod template;
 mod tui;
+mod util;
 
 use anyhow::Result;
 use clap::Parser;
 use tracing_subscriber::EnvFilter;
 
-use cli::{Cli, Commands};
-use commands::show_statusline;
-use data_loader::DataLoader;
+use cli::{Cli, Commands, DashboardArgs};
+use currency::Currency;
+use commands::{
+    BurnWindow, export_stats, show_compare, show_completions, show_hourly, show_metrics, show_models, show_paths,
+    show_statusline, show_statusline_json, show_statusline_schema, show_summary, show_timeseries, show_weekday,
+    simulate_model, verify_costs, watch_statusline, watch_summary,
+};
+use data_loader::{DataLoader, LoadOptions};
 use tui::{App, run_dashboard};
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize logging
+    let cli = Cli::parse();
+    
+    // Initialize logging; an explicit RUST_LOG overrides -v/-q
     tracing_subscriber::fmt()
         .with_env_filter(
             EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| EnvFilter::new("warn"))
+                .unwrap_or_else(|_| EnvFilter::new(cli.log_level()))
         )
+        .with_writer(std::io::stderr)
         .init();
     
-    let cli = Cli::parse();
+    let color = cli.color.enabled();
+    util::set_compact_json(cli.compact);
+    util::set_exclude_cache_read(cli.exclude_cache_read);
+    util::set_footer_style(cli.footer_style);
+    util::set_ascii(cli.ascii_enabled());
+    if let Some(places) = cli.round {
+        util::set_cost_decimals(places);
+    }
+    if let Some(path) = &cli.output_file {
+        util::set_output_file(path);
+    }
+    util::set_thousands_separator(match cli.thousands_sep {
+        Some(separator) => separator.as_char(),
+        None => util::locale_thousands_separator(),
+    });
+    let currency = Currency::new(&cli.currency, cli.rate)?;
+    let options = LoadOptions {
+        include_synthetic: cli.include_synthetic,
+        include_empty: cli.include_empty,
+        no_dedupe_cache: cli.no_dedupe_cache,
+        merge_sessions: cli.merge_sessions,
+        profile: cli.profile,
+        price_overrides: cli.price.clone(),
+        anonymize: cli.anonymize,
+        allow_future: cli.allow_future,
+    };
     
     match cli.command {
-        Some(Commands::Dashboard) | None => {
+        Some(Commands::Dashboard(args)) => {
+            launch_dashboard(args, color, currency, &options)?;
+        }
+        None => {
             // Dashboard is the default command
-            let loader = DataLoader::new()?;
-            let stats = loader.load_all_usage()?;
-            let app = App::new(stats);
-            run_dashboard(app)?;
+            launch_dashboard(DashboardArgs::default(), color, currency, &options)?;
+        }
+        Some(Commands::Statusline { stdin, json, watch, burn_window, completed_hours, plan, explain }) => {
+            let burn = BurnWindow { hours: burn_window, completed_only: completed_hours };
+            match watch {
+                _ if explain => show_statusline_schema()?,
+                Some(interval) => {
+                    watch_statusline(stdin, interval, burn, plan, json, color, &currency, &options).await?
+                }
+                None if json => show_statusline_json(stdin, burn, plan, &options)?,
+                None => show_statusline(stdin, burn, plan, color, &currency, &options)?,
+            }
+        }
+        Some(Commands::Summary { json, watch, stdin, fail_over, by_source, template }) => {
+            match watch {
+                Some(interval) => watch_summary(json, interval, by_source, &currency, &options).await?,
+                None => show_summary(json, stdin, fail_over, by_source, template.as_deref(), &currency, &options)?,
+            }
+        }
+        Some(Commands::Hourly { json, stdin }) => {
+            show_hourly(json, stdin, &currency, &options)?;
+        }
+        Some(Commands::Weekday { json, stdin }) => {
+            show_weekday(json, stdin, &currency, &options)?;
+        }
+        Some(Commands::Metrics { stdin }) => {
+            show_metrics(stdin, &options)?;
         }
-        Some(Commands::Statusline { stdin }) => {
-            show_statusline(stdin)?;
+        Some(Commands::Timeseries { format, stdin }) => {
+            show_timeseries(format, stdin, &options)?;
+        }
+        Some(Commands::Simulate { model, this_month, json, stdin }) => {
+            simulate_model(&model, this_month, json, stdin, &currency, &options)?;
+        }
+        Some(Commands::Verify { tolerance, json, stdin }) => {
+            verify_costs(json, tolerance, stdin, &options)?;
+        }
+        Some(Commands::Paths { json }) => {
+            show_paths(json, options.anonymize)?;
+        }
+        Some(Commands::Export { output, pretty, ndjson, stdin }) => {
+            export_stats(&output, pretty, ndjson, stdin, &options)?;
+        }
+        Some(Commands::Models { json, by_family }) => {
+            show_models(json, by_family, &currency, &options)?;
+        }
+        Some(Commands::Compare { period, json }) => {
+            show_compare(period, json, &currency, &options)?;
+        }
+        Some(Commands::Completions { shell }) => {
+            show_completions(shell)?;
         }
     }
     
     Ok(())
+}
+
+fn launch_dashboard(args: DashboardArgs, color: bool, currency: Currency, options: &LoadOptions) -> Result<()> {
+    let loader = DataLoader::new(options)?;
+    let stats = loader.load_all_usage()?;
+    let mut app = App::new(stats, args.unit, args.order, color, currency);
+    app.data_dirs = loader.data_dirs();
+    app.basename_only = args.basename;
+    app.projects_root = args.projects_root;
+    app.relative_time = args.relative;
+    app.detailed_tokens = args.detailed;
+    app.monthly_spark = args.spark;
+    app.by_family = args.by_family;
+    app.top_projects = args.top_projects;
+    app.confirm_quit = !args.no_confirm_quit;
+    if let Some(warn) = args.cost_warn {
+        app.cost_warn = warn;
+    }
+    if let Some(high) = args.cost_high {
+        app.cost_high = high;
+    }
+    run_dashboard(app)
 }
\ No newline at end of file
@@ -0,0 +1,127 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::models::{ModelUsage, UsageStats};
+
+/// Model id prefixes and their family labels, most specific first
+const FAMILIES: &[(&str, &str)] = &[
+    ("claude-opus-4-5", "Opus 4.5"),
+    ("claude-opus-4-1", "Opus 4.1"),
+    ("claude-opus-4", "Opus 4"),
+    ("claude-sonnet-4-5", "Sonnet 4.5"),
+    ("claude-sonnet-4", "Sonnet 4"),
+    ("claude-haiku-4-5", "Haiku 4.5"),
+    ("claude-3-7-sonnet", "Sonnet 3.7"),
+    ("claude-3-5-sonnet", "Sonnet 3.5"),
+    ("claude-3-5-haiku", "Haiku 3.5"),
+    ("claude-3-opus", "Opus 3"),
+    ("claude-3-sonnet", "Sonnet 3"),
+    ("claude-3-haiku", "Haiku 3"),
+];
+
+/// Usage for every model id in one family
+#[derive(Debug, Clone, Serialize)]
+pub struct FamilyUsage {
+    /// Lifetime totals, with `model` set to the family label
+    #[serde(flatten)]
+    pub usage: ModelUsage,
+    /// Raw model ids grouped into this family
+    pub models: Vec<String>,
+}
+
+/// Family label for a raw model id, e.g. "Sonnet 3.5" for
+/// `claude-3-5-sonnet-20241022`. Unknown ids fall back to the id without
+/// its date suffix.
+pub fn model_family(model: &str) -> String {
+    if let Some((_, family)) = FAMILIES.iter().find(|(prefix, _)| in_family(model, prefix)) {
+        return family.to_string();
+    }
+
+    match model.rsplit_once('-') {
+        Some((base, date)) if date.len() == 8 && date.bytes().all(|b| b.is_ascii_digit()) => base.to_string(),
+        _ => model.to_string(),
+    }
+}
+
+/// Whether `model` is `prefix` exactly or `prefix` followed by a date or other
+/// suffix, but not by a minor version: `claude-sonnet-4-6` isn't Sonnet 4.
+fn in_family(model: &str, prefix: &str) -> bool {
+    match model.strip_prefix(prefix) {
+        Some("") => true,
+        Some(rest) => match rest.strip_prefix('-') {
+            Some(rest) => {
+                let segment = rest.split('-').next().unwrap_or_default();
+                !(segment.len() <= 2 && segment.bytes().all(|b| b.is_ascii_digit()))
+            }
+            None => false,
+        },
+        None => false,
+    }
+}
+
+impl UsageStats {
+    /// Per-model totals merged by family, sorted by cost, highest first
+    pub fn models_by_family(&self) -> Vec<FamilyUsage> {
+        let mut families: BTreeMap<String, FamilyUsage> = BTreeMap::new();
+
+        for model in &self.models {
+            let family = model_family(&model.model);
+            let entry = families.entry(family.clone()).or_insert_with(|| FamilyUsage {
+                usage: ModelUsage {
+                    model: family,
+                    session_count: 0,
+                    ..model.clone()
+                },
+                models: Vec::new(),
+            });
+            if !entry.models.is_empty() {
+                entry.usage.tokens.add(&model.tokens);
+                entry.usage.total_cost += model.total_cost;
+                entry.usage.first_seen = entry.usage.first_seen.min(model.first_seen);
+                entry.usage.last_seen = entry.usage.last_seen.max(model.last_seen);
+            }
+            entry.models.push(model.model.clone());
+        }
+
+        // Sessions can use several ids of one family, so count them afresh
+        let mut families: Vec<_> = families.into_values().collect();
+        for family in &mut families {
+            family.usage.session_count = self.sessions.iter()
+                .filter(|s| s.models_used.iter().any(|m| family.models.contains(m)))
+                .count();
+        }
+        families.sort_by(|a, b| b.usage.total_cost.total_cmp(&a.usage.total_cost));
+        families
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn model_ids_map_to_their_family() {
+        let cases = [
+            ("claude-opus-4-5-20251101", "Opus 4.5"),
+            ("claude-opus-4-1-20250805", "Opus 4.1"),
+            ("claude-opus-4-20250514", "Opus 4"),
+            ("claude-sonnet-4-5-20250929", "Sonnet 4.5"),
+            ("claude-sonnet-4-5", "Sonnet 4.5"),
+            ("claude-sonnet-4-20250514", "Sonnet 4"),
+            ("claude-haiku-4-5-20251001", "Haiku 4.5"),
+            ("claude-3-7-sonnet-20250219", "Sonnet 3.7"),
+            ("claude-3-5-sonnet-20241022", "Sonnet 3.5"),
+            ("claude-3-5-haiku-20241022", "Haiku 3.5"),
+            ("claude-3-opus-20240229", "Opus 3"),
+            ("claude-3-haiku-20240307", "Haiku 3"),
+            // Unknown versions fall back to the id without its date
+            ("claude-sonnet-4-6-20260101", "claude-sonnet-4-6"),
+            ("claude-opus-40", "claude-opus-40"),
+            ("gpt-4o", "gpt-4o"),
+        ];
+
+        for (model, family) in cases {
+            assert_eq!(model_family(model), family, "{}", model);
+        }
+    }
+}
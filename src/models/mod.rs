@@ -1,5 +1,7 @@
 pub mod usage;
 pub mod pricing;
+pub mod family;
 
 pub use usage::*;
-pub use pricing::*;
\ No newline at end of file
+pub use pricing::*;
+pub use family::*;
\ No newline at end of file
@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Requests with more input tokens than this are billed at the long-context tier
+pub const LONG_CONTEXT_THRESHOLD: u64 = 200_000;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ModelPricing {
     pub input_cost_per_token: f64,
     pub output_cost_per_token: f64,
@@ -9,21 +12,120 @@ pub struct ModelPricing {
     pub cache_creation_input_token_cost: f64,
     #[serde(default)]
     pub cache_read_input_token_cost: f64,
+    // Long-context (1M) tier rates; `None` means the model has no separate tier
+    #[serde(default)]
+    pub input_cost_per_token_above_200k: Option<f64>,
+    #[serde(default)]
+    pub output_cost_per_token_above_200k: Option<f64>,
+    #[serde(default)]
+    pub cache_creation_input_token_cost_above_200k: Option<f64>,
+    #[serde(default)]
+    pub cache_read_input_token_cost_above_200k: Option<f64>,
+}
+
+/// Cost split by token category
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CostBreakdown {
+    pub input: f64,
+    pub output: f64,
+    pub cache_creation: f64,
+    pub cache_read: f64,
+}
+
+impl CostBreakdown {
+    pub fn total(&self) -> f64 {
+        self.input + self.output + self.cache_creation + self.cache_read
+    }
+    
+    pub fn add(&mut self, other: &CostBreakdown) {
+        self.input += other.input;
+        self.output += other.output;
+        self.cache_creation += other.cache_creation;
+        self.cache_read += other.cache_read;
+    }
 }
 
 impl ModelPricing {
-    pub fn calculate_cost(&self, tokens: &crate::models::TokenUsage) -> f64 {
-        let input_cost = tokens.input_tokens as f64 * self.input_cost_per_token;
-        let output_cost = tokens.output_tokens as f64 * self.output_cost_per_token;
-        let cache_creation_cost = tokens.cache_creation_input_tokens as f64 * self.cache_creation_input_token_cost;
-        let cache_read_cost = tokens.cache_read_input_tokens as f64 * self.cache_read_input_token_cost;
+    /// Whether a single request's input puts it in the long-context tier. Must be
+    /// checked per request, since summed usage says nothing about context size.
+    pub fn is_long_context(request: &crate::models::TokenUsage) -> bool {
+        request.total_input() > LONG_CONTEXT_THRESHOLD
+    }
+    
+    pub fn cost_breakdown(&self, tokens: &crate::models::TokenUsage, long_context: bool) -> CostBreakdown {
+        // Fall back to the standard rate for any category without a long-context price
+        let rate = |standard: f64, above_200k: Option<f64>| {
+            if long_context { above_200k.unwrap_or(standard) } else { standard }
+        };
         
-        input_cost + output_cost + cache_creation_cost + cache_read_cost
+        CostBreakdown {
+            input: tokens.input_tokens as f64
+                * rate(self.input_cost_per_token, self.input_cost_per_token_above_200k),
+            output: tokens.output_tokens as f64
+                * rate(self.output_cost_per_token, self.output_cost_per_token_above_200k),
+            cache_creation: tokens.cache_creation_input_tokens as f64
+                * rate(self.cache_creation_input_token_cost, self.cache_creation_input_token_cost_above_200k),
+            cache_read: tokens.cache_read_input_tokens as f64
+                * rate(self.cache_read_input_token_cost, self.cache_read_input_token_cost_above_200k),
+        }
+    }
+    
+    pub fn calculate_cost(&self, tokens: &crate::models::TokenUsage, long_context: bool) -> f64 {
+        self.cost_breakdown(tokens, long_context).total()
+    }
+}
+
+/// One `--price MODEL=INPUT,OUTPUT[,CACHE_WRITE,CACHE_READ]` flag, rates in USD
+/// per million tokens
+#[derive(Debug, Clone)]
+pub struct PriceOverride {
+    /// Model id, or a prefix of ids, e.g. `claude-opus-4`
+    pub model: String,
+    pub pricing: ModelPricing,
+}
+
+impl std::str::FromStr for PriceOverride {
+    type Err = String;
+    
+    /// Without explicit cache rates, cache writes cost 1.25x and cache reads
+    /// 0.1x the input rate, as on Anthropic's published pricing
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (model, rates) = spec.split_once('=')
+            .ok_or_else(|| format!("expected MODEL=INPUT,OUTPUT[,CACHE_WRITE,CACHE_READ], got `{}`", spec))?;
+        let model = model.trim();
+        if model.is_empty() {
+            return Err(format!("missing model name in `{}`", spec));
+        }
+        
+        let rates = rates.split(',')
+            .map(|rate| match rate.trim().parse::<f64>() {
+                Ok(rate) if rate.is_finite() && rate >= 0.0 => Ok(rate / 1_000_000.0),
+                _ => Err(format!("`{}` is not a non-negative price per million tokens", rate.trim())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let (input, output, cache_creation, cache_read) = match rates[..] {
+            [input, output] => (input, output, input * 1.25, input * 0.1),
+            [input, output, cache_creation, cache_read] => (input, output, cache_creation, cache_read),
+            _ => return Err(format!("expected 2 or 4 rates for `{}`, got {}", model, rates.len())),
+        };
+        
+        Ok(Self {
+            model: model.to_string(),
+            pricing: ModelPricing {
+                input_cost_per_token: input,
+                output_cost_per_token: output,
+                cache_creation_input_token_cost: cache_creation,
+                cache_read_input_token_cost: cache_read,
+                ..Default::default()
+            },
+        })
     }
 }
 
 pub struct PricingData {
     models: HashMap<String, ModelPricing>,
+    /// `--price` rates, checked before `models`
+    overrides: Vec<PriceOverride>,
 }
 
 impl PricingData {
@@ -36,6 +138,7 @@ impl PricingData {
             output_cost_per_token: 15.0 / 1_000_000.0,
             cache_creation_input_token_cost: 3.75 / 1_000_000.0,
             cache_read_input_token_cost: 0.30 / 1_000_000.0,
+            ..Default::default()
         });
         
         // Claude 3.5 Haiku pricing
@@ -44,6 +147,7 @@ impl PricingData {
             output_cost_per_token: 5.0 / 1_000_000.0,
             cache_creation_input_token_cost: 1.25 / 1_000_000.0,
             cache_read_input_token_cost: 0.10 / 1_000_000.0,
+            ..Default::default()
         });
         
         // Claude 3 Opus pricing
@@ -52,14 +156,19 @@ impl PricingData {
             output_cost_per_token: 75.0 / 1_000_000.0,
             cache_creation_input_token_cost: 18.75 / 1_000_000.0,
             cache_read_input_token_cost: 1.50 / 1_000_000.0,
+            ..Default::default()
         });
         
-        // Claude 4 Sonnet pricing (using same as 3.5 Sonnet for now)
+        // Claude 4 Sonnet pricing, with premium rates for >200k-token requests
         models.insert("claude-sonnet-4-20250514".to_string(), ModelPricing {
             input_cost_per_token: 3.0 / 1_000_000.0,
             output_cost_per_token: 15.0 / 1_000_000.0,
             cache_creation_input_token_cost: 3.75 / 1_000_000.0,
             cache_read_input_token_cost: 0.30 / 1_000_000.0,
+            input_cost_per_token_above_200k: Some(6.0 / 1_000_000.0),
+            output_cost_per_token_above_200k: Some(22.50 / 1_000_000.0),
+            cache_creation_input_token_cost_above_200k: Some(7.50 / 1_000_000.0),
+            cache_read_input_token_cost_above_200k: Some(0.60 / 1_000_000.0),
         });
         
         // Claude 4 Opus pricing (using same as Claude 3 Opus for now)
@@ -68,6 +177,7 @@ impl PricingData {
             output_cost_per_token: 75.0 / 1_000_000.0,
             cache_creation_input_token_cost: 18.75 / 1_000_000.0,
             cache_read_input_token_cost: 1.50 / 1_000_000.0,
+            ..Default::default()
         });
         
         // Claude 4.1 Opus pricing (using same as Claude 3 Opus for now)
@@ -76,18 +186,136 @@ impl PricingData {
             output_cost_per_token: 75.0 / 1_000_000.0,
             cache_creation_input_token_cost: 18.75 / 1_000_000.0,
             cache_read_input_token_cost: 1.50 / 1_000_000.0,
+            ..Default::default()
         });
         
-        Self { models }
+        Self { models, overrides: Vec::new() }
+    }
+    
+    /// Price `model_prefix` and every model id starting with it at `pricing`;
+    /// where several overrides match, the longest prefix wins
+    pub fn set_override(&mut self, price: PriceOverride) {
+        self.overrides.retain(|existing| existing.model != price.model);
+        self.overrides.push(price);
+    }
+    
+    fn override_for(&self, model: &str) -> Option<&ModelPricing> {
+        self.overrides.iter()
+            .filter(|price| model.starts_with(&price.model))
+            .max_by_key(|price| price.model.len())
+            .map(|price| &price.pricing)
+    }
+    
+    /// Whether `--price` set this model's rates, so logged costs shouldn't be trusted
+    pub fn is_overridden(&self, model: &str) -> bool {
+        self.override_for(model).is_some()
+    }
+    
+    /// Ids of the bundled models, sorted
+    pub fn model_ids(&self) -> Vec<&str> {
+        let mut ids: Vec<_> = self.models.keys().map(String::as_str).collect();
+        ids.sort_unstable();
+        ids
     }
     
     pub fn get_pricing(&self, model: &str) -> Option<&ModelPricing> {
-        self.models.get(model)
+        self.override_for(model).or_else(|| self.models.get(model))
     }
     
-    pub fn calculate_cost(&self, model: &str, tokens: &crate::models::TokenUsage) -> f64 {
+    pub fn cost_breakdown(&self, model: &str, tokens: &crate::models::TokenUsage, long_context: bool) -> CostBreakdown {
         self.get_pricing(model)
-            .map(|p| p.calculate_cost(tokens))
+            .map(|p| p.cost_breakdown(tokens, long_context))
+            .unwrap_or_default()
+    }
+    
+    pub fn calculate_cost(&self, model: &str, tokens: &crate::models::TokenUsage, long_context: bool) -> f64 {
+        self.get_pricing(model)
+            .map(|p| p.calculate_cost(tokens, long_context))
             .unwrap_or(0.0)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TokenUsage;
+
+    fn per_mtok(rate: f64) -> f64 {
+        rate * 1_000_000.0
+    }
+
+    fn assert_rate(actual: f64, expected_per_mtok: f64) {
+        assert!((per_mtok(actual) - expected_per_mtok).abs() < 1e-9, "{} != {}", per_mtok(actual), expected_per_mtok);
+    }
+
+    #[test]
+    fn two_rates_derive_cache_rates_from_input() {
+        let price: PriceOverride = "claude-opus-4 = 10, 50".parse().unwrap();
+        assert_eq!(price.model, "claude-opus-4");
+        assert_rate(price.pricing.input_cost_per_token, 10.0);
+        assert_rate(price.pricing.output_cost_per_token, 50.0);
+        assert_rate(price.pricing.cache_creation_input_token_cost, 12.5);
+        assert_rate(price.pricing.cache_read_input_token_cost, 1.0);
+        assert!(price.pricing.input_cost_per_token_above_200k.is_none());
+    }
+
+    #[test]
+    fn four_rates_set_cache_rates_explicitly() {
+        let price: PriceOverride = "my-model=1,2,3,0.5".parse().unwrap();
+        assert_rate(price.pricing.input_cost_per_token, 1.0);
+        assert_rate(price.pricing.output_cost_per_token, 2.0);
+        assert_rate(price.pricing.cache_creation_input_token_cost, 3.0);
+        assert_rate(price.pricing.cache_read_input_token_cost, 0.5);
+    }
+
+    #[test]
+    fn invalid_price_specs_are_rejected() {
+        for spec in [
+            "claude-opus-4=-1,5",
+            "claude-opus-4=1,NaN",
+            "claude-opus-4=inf,5",
+            "claude-opus-4=1,five",
+            "claude-opus-4=1",
+            "claude-opus-4=1,2,3",
+            "claude-opus-4=1,2,3,4,5",
+            "=1,2",
+            "claude-opus-4",
+        ] {
+            assert!(spec.parse::<PriceOverride>().is_err(), "{}", spec);
+        }
+    }
+
+    #[test]
+    fn longest_matching_override_wins() {
+        let mut pricing = PricingData::new();
+        pricing.set_override("claude-opus-4=1,1".parse().unwrap());
+        pricing.set_override("claude-opus-4-1=2,2".parse().unwrap());
+        pricing.set_override("claude=9,9".parse().unwrap());
+
+        let input_rate = |model: &str| per_mtok(pricing.get_pricing(model).unwrap().input_cost_per_token);
+        assert_eq!(input_rate("claude-opus-4-1-20250805"), 2.0);
+        assert_eq!(input_rate("claude-opus-4-20250514"), 1.0);
+        assert_eq!(input_rate("claude-sonnet-4-20250514"), 9.0);
+        assert!(pricing.is_overridden("claude-opus-4-20250514"));
+        assert!(!pricing.is_overridden("gpt-4o"));
+    }
+
+    #[test]
+    fn repeating_a_model_replaces_its_override() {
+        let mut pricing = PricingData::new();
+        pricing.set_override("claude-opus-4=1,1".parse().unwrap());
+        pricing.set_override("claude-opus-4=3,3".parse().unwrap());
+
+        let tokens = TokenUsage { input_tokens: 1_000_000, ..TokenUsage::default() };
+        assert_eq!(pricing.calculate_cost("claude-opus-4-20250514", &tokens, false), 3.0);
+    }
+
+    #[test]
+    fn overrides_have_no_long_context_tier() {
+        let mut pricing = PricingData::new();
+        pricing.set_override("claude-sonnet-4=2,10".parse().unwrap());
+
+        let tokens = TokenUsage { input_tokens: 1_000_000, ..TokenUsage::default() };
+        assert_eq!(pricing.calculate_cost("claude-sonnet-4-20250514", &tokens, true), 2.0);
+    }
+}
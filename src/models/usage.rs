@@ -1,6 +1,12 @@
-use chrono::{DateTime, Utc, NaiveDate};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+
+use crate::models::CostBreakdown;
+use crate::util::token_total;
+
+/// Trailing active days a day's cost is compared against for anomaly detection
+const ANOMALY_WINDOW_DAYS: usize = 14;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsageEntry {
@@ -10,6 +16,20 @@ pub struct UsageEntry {
     pub version: Option<String>,
     pub cwd: Option<String>,
     pub message: Message,
+    #[serde(rename = "isApiErrorMessage", default)]
+    pub is_api_error_message: bool,
+    /// Data directory the transcript was read from; not part of the JSONL
+    #[serde(skip)]
+    pub source_root: Option<String>,
+}
+
+impl UsageEntry {
+    /// Placeholder entries Claude writes for API errors and interrupted turns,
+    /// e.g. model `<synthetic>`, rather than real model responses
+    pub fn is_synthetic(&self) -> bool {
+        self.message.model.starts_with('<')
+            || (self.is_api_error_message && self.message.usage.total() == 0)
+    }
 }
 
 
@@ -37,18 +57,37 @@ pub struct TokenUsage {
 
 impl TokenUsage {
     pub fn total_input(&self) -> u64 {
-        self.input_tokens + self.cache_creation_input_tokens + self.cache_read_input_tokens
+        self.input_tokens
+            .saturating_add(self.cache_creation_input_tokens)
+            .saturating_add(self.cache_read_input_tokens)
     }
 
     pub fn total(&self) -> u64 {
-        self.total_input() + self.output_tokens
+        self.total_input().saturating_add(self.output_tokens)
+    }
+    
+    /// Total without cache reads, which are billed at a fraction of the input rate
+    pub fn total_billable(&self) -> u64 {
+        self.total().saturating_sub(self.cache_read_input_tokens)
+    }
+
+    /// Share of input tokens served from cache, from 0.0 to 1.0
+    pub fn cache_hit_rate(&self) -> f64 {
+        let total_input = self.total_input();
+        if total_input == 0 {
+            return 0.0;
+        }
+        self.cache_read_input_tokens as f64 / total_input as f64
     }
 
     pub fn add(&mut self, other: &TokenUsage) {
-        self.input_tokens += other.input_tokens;
-        self.output_tokens += other.output_tokens;
-        self.cache_creation_input_tokens += other.cache_creation_input_tokens;
-        self.cache_read_input_tokens += other.cache_read_input_tokens;
+        // Saturate rather than overflow so a corrupt line can't panic or wrap the totals
+        self.input_tokens = self.input_tokens.saturating_add(other.input_tokens);
+        self.output_tokens = self.output_tokens.saturating_add(other.output_tokens);
+        self.cache_creation_input_tokens = self.cache_creation_input_tokens
+            .saturating_add(other.cache_creation_input_tokens);
+        self.cache_read_input_tokens = self.cache_read_input_tokens
+            .saturating_add(other.cache_read_input_tokens);
     }
 }
 
@@ -57,34 +96,397 @@ pub struct DailyUsage {
     pub date: NaiveDate,
     pub tokens: TokenUsage,
     pub total_cost: f64,
+    pub cost_breakdown: CostBreakdown,
     pub models_used: HashSet<String>,
     pub session_count: usize,
 }
 
+/// One model's usage on one day
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyModelUsage {
+    pub date: NaiveDate,
+    pub model: String,
+    pub tokens: TokenUsage,
+    pub total_cost: f64,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct SessionUsage {
     pub session_id: String,
     pub project_path: String,
+    /// Data directory the session was read from; `None` for stdin
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_root: Option<String>,
     pub tokens: TokenUsage,
     pub total_cost: f64,
+    pub first_activity: DateTime<Utc>,
     pub last_activity: DateTime<Utc>,
     pub models_used: HashSet<String>,
 }
 
+impl SessionUsage {
+    pub fn duration(&self) -> chrono::Duration {
+        self.last_activity - self.first_activity
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct MonthlyUsage {
     pub month: String, // YYYY-MM format
     pub tokens: TokenUsage,
     pub total_cost: f64,
+    pub cost_breakdown: CostBreakdown,
     pub models_used: HashSet<String>,
     pub daily_breakdown: Vec<DailyUsage>,
 }
 
-#[derive(Debug, Clone)]
+impl MonthlyUsage {
+    /// Linear end-of-month cost projection from spend so far. Returns `None`
+    /// on the first day of the month when there isn't enough data to project.
+    pub fn projected_cost(&self, today: NaiveDate) -> Option<f64> {
+        let elapsed = today.day();
+        if elapsed <= 1 {
+            return None;
+        }
+        
+        let first = today.with_day(1)?;
+        let next_month = first.checked_add_months(chrono::Months::new(1))?;
+        let days_in_month = (next_month - first).num_days() as f64;
+        
+        Some(self.total_cost / elapsed as f64 * days_in_month)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelUsage {
+    pub model: String,
+    pub tokens: TokenUsage,
+    pub total_cost: f64,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub session_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HourlyUsage {
+    pub hour: DateTime<Utc>, // Start of the hour
+    pub tokens: TokenUsage,
+    pub total_cost: f64,
+}
+
+/// Usage within one clock hour of local time. `HourlyUsage` buckets are UTC
+/// hours, which straddle two local hours in zones with a half-hour offset.
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalHourUsage {
+    pub date: NaiveDate,
+    /// 0-23
+    pub hour: u32,
+    pub tokens: TokenUsage,
+    pub total_cost: f64,
+}
+
+/// Derived per-session and per-day averages; zero when there's no data
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Averages {
+    pub cost_per_session: f64,
+    pub tokens_per_session: f64,
+    pub cost_per_active_day: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct UsageStats {
     pub total_tokens: TokenUsage,
     pub total_cost: f64,
+    /// Pricing-table cost per token category; may differ from `total_cost`
+    /// when entries carry a logged costUSD
+    pub cost_breakdown: CostBreakdown,
     pub sessions: Vec<SessionUsage>,
     pub daily: Vec<DailyUsage>,
     pub monthly: Vec<MonthlyUsage>,
-}
\ No newline at end of file
+    pub hourly: Vec<HourlyUsage>,
+    pub models: Vec<ModelUsage>, // Sorted by cost, highest first
+    /// Sorted by date, then model
+    pub daily_models: Vec<DailyModelUsage>,
+    /// Sorted by local date, then hour
+    pub local_hours: Vec<LocalHourUsage>,
+}
+
+impl UsageStats {
+    pub fn is_empty(&self) -> bool {
+        self.daily.is_empty()
+    }
+    
+    pub fn averages(&self) -> Averages {
+        let sessions = self.sessions.len() as f64;
+        let days = self.daily.len() as f64;
+        
+        Averages {
+            cost_per_session: if sessions > 0.0 { self.total_cost / sessions } else { 0.0 },
+            tokens_per_session: if sessions > 0.0 { token_total(&self.total_tokens) as f64 / sessions } else { 0.0 },
+            cost_per_active_day: if days > 0.0 { self.total_cost / days } else { 0.0 },
+        }
+    }
+    
+    /// Days costing more than two standard deviations above the mean of the
+    /// preceding 14 active days. Days without that much history are never flagged.
+    pub fn cost_anomalies(&self) -> HashSet<NaiveDate> {
+        self.daily.windows(ANOMALY_WINDOW_DAYS + 1)
+            .filter_map(|window| {
+                let (history, day) = window.split_at(ANOMALY_WINDOW_DAYS);
+                let day = &day[0];
+                let n = history.len() as f64;
+                let mean = history.iter().map(|d| d.total_cost).sum::<f64>() / n;
+                let variance = history.iter()
+                    .map(|d| (d.total_cost - mean).powi(2))
+                    .sum::<f64>() / n;
+                (day.total_cost > mean + 2.0 * variance.sqrt()).then_some(day.date)
+            })
+            .collect()
+    }
+    
+    /// Copy limited to the days `from..=to`. Days and hours are filtered, and
+    /// months, models and totals rebuilt from the days in range. Sessions are
+    /// kept when their activity overlaps the range, but their own totals stay
+    /// whole since they aren't split by day.
+    pub fn between(&self, from: NaiveDate, to: NaiveDate) -> UsageStats {
+        let in_range = |date: NaiveDate| (from..=to).contains(&date);
+        let overlaps = |first: &DateTime<Utc>, last: &DateTime<Utc>| {
+            first.date_naive() <= to && last.date_naive() >= from
+        };
+        
+        let daily: Vec<_> = self.daily.iter().filter(|d| in_range(d.date)).cloned().collect();
+        let monthly = self.monthly.iter()
+            .filter_map(|m| {
+                let days: Vec<_> = m.daily_breakdown.iter().filter(|d| in_range(d.date)).cloned().collect();
+                if days.is_empty() {
+                    return None;
+                }
+                let mut month = MonthlyUsage {
+                    month: m.month.clone(),
+                    tokens: TokenUsage::default(),
+                    total_cost: 0.0,
+                    cost_breakdown: CostBreakdown::default(),
+                    models_used: HashSet::new(),
+                    daily_breakdown: Vec::new(),
+                };
+                for day in &days {
+                    month.tokens.add(&day.tokens);
+                    month.total_cost += day.total_cost;
+                    month.cost_breakdown.add(&day.cost_breakdown);
+                    month.models_used.extend(day.models_used.iter().cloned());
+                }
+                month.daily_breakdown = days;
+                Some(month)
+            })
+            .collect();
+        
+        let sessions: Vec<_> = self.sessions.iter()
+            .filter(|s| overlaps(&s.first_activity, &s.last_activity))
+            .cloned()
+            .collect();
+        let daily_models: Vec<_> = self.daily_models.iter().filter(|d| in_range(d.date)).cloned().collect();
+        
+        // First and last use stay lifetime values, as models aren't timestamped per day
+        let mut models: Vec<ModelUsage> = self.models.iter()
+            .filter_map(|m| {
+                let days: Vec<_> = daily_models.iter().filter(|d| d.model == m.model).collect();
+                if days.is_empty() {
+                    return None;
+                }
+                let mut model = ModelUsage {
+                    tokens: TokenUsage::default(),
+                    total_cost: 0.0,
+                    session_count: sessions.iter().filter(|s| s.models_used.contains(&m.model)).count(),
+                    ..m.clone()
+                };
+                for day in days {
+                    model.tokens.add(&day.tokens);
+                    model.total_cost += day.total_cost;
+                }
+                Some(model)
+            })
+            .collect();
+        models.sort_by(|a, b| b.total_cost.total_cmp(&a.total_cost));
+        
+        let mut total_tokens = TokenUsage::default();
+        let mut cost_breakdown = CostBreakdown::default();
+        for day in &daily {
+            total_tokens.add(&day.tokens);
+            cost_breakdown.add(&day.cost_breakdown);
+        }
+        
+        UsageStats {
+            total_tokens,
+            total_cost: daily.iter().map(|d| d.total_cost).sum(),
+            cost_breakdown,
+            sessions,
+            daily,
+            monthly,
+            hourly: self.hourly.iter().filter(|h| in_range(h.hour.date_naive())).cloned().collect(),
+            models,
+            daily_models,
+            local_hours: self.local_hours.iter().filter(|h| in_range(h.date)).cloned().collect(),
+        }
+    }
+    
+    /// Session cost summed per project path, highest first
+    pub fn cost_by_project(&self) -> Vec<(&str, f64)> {
+        let mut projects: HashMap<&str, f64> = HashMap::new();
+        for session in &self.sessions {
+            *projects.entry(session.project_path.as_str()).or_default() += session.total_cost;
+        }
+        let mut projects: Vec<_> = projects.into_iter().collect();
+        projects.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        projects
+    }
+    
+    pub fn get_today_stats(&self) -> Option<&DailyUsage> {
+        let today = Local::now().date_naive();
+        self.daily.iter().find(|d| d.date == today)
+    }
+    
+    /// First and last days with usage and the number of calendar days they span
+    pub fn date_range(&self) -> Option<(NaiveDate, NaiveDate, i64)> {
+        // `daily` is sorted by date
+        let first = self.daily.first()?.date;
+        let last = self.daily.last()?.date;
+        Some((first, last, (last - first).num_days() + 1))
+    }
+    
+    /// Sessions with activity on today's date (local time), including ones that
+    /// started earlier and ran past midnight
+    pub fn sessions_today(&self) -> usize {
+        let today = Local::now().date_naive();
+        self.sessions.iter()
+            .filter(|s| {
+                s.first_activity.with_timezone(&Local).date_naive() <= today
+                    && s.last_activity.with_timezone(&Local).date_naive() >= today
+            })
+            .count()
+    }
+    
+    /// Whether any session logged usage within `window` of now
+    pub fn active_within(&self, window: Duration) -> bool {
+        let since = Utc::now() - window;
+        self.sessions.iter().any(|s| s.last_activity >= since)
+    }
+    
+    /// Tokens and cost over the last 7 days, including today
+    pub fn get_week_stats(&self) -> (TokenUsage, f64) {
+        self.get_rolling_stats(7)
+    }
+    
+    /// Cost breakdown over the trailing `days` days, including today
+    pub fn get_rolling_cost_breakdown(&self, days: i64) -> CostBreakdown {
+        let today = Local::now().date_naive();
+        let start = today - Duration::days(days);
+        
+        self.daily.iter()
+            .filter(|d| d.date > start)
+            .fold(CostBreakdown::default(), |mut breakdown, d| {
+                breakdown.add(&d.cost_breakdown);
+                breakdown
+            })
+    }
+    
+    /// Tokens and cost over the trailing `days` days, including today,
+    /// regardless of month boundaries
+    pub fn get_rolling_stats(&self, days: i64) -> (TokenUsage, f64) {
+        let today = Local::now().date_naive();
+        let start = today - Duration::days(days);
+        
+        self.daily.iter()
+            .filter(|d| d.date > start)
+            .fold((TokenUsage::default(), 0.0), |(mut tokens, cost), d| {
+                tokens.add(&d.tokens);
+                (tokens, cost + d.total_cost)
+            })
+    }
+    
+    pub fn get_month_stats(&self) -> Option<&MonthlyUsage> {
+        let today = Local::now().date_naive();
+        let current_month = format!("{:04}-{:02}", today.year(), today.month());
+        self.monthly.iter().find(|m| m.month == current_month)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_loader::test_support::{aggregate, at, entry, usage};
+    use crate::data_loader::LoadOptions;
+
+    /// Stats with one request per listed June day, costing $3 per million input tokens
+    fn days(inputs: &[(u32, u64)]) -> UsageStats {
+        let entries = inputs.iter()
+            .map(|&(day, input)| entry("a", at(day, 12, 0), usage(input, 0, 0, 0)))
+            .collect();
+        aggregate(&LoadOptions::default(), entries)
+    }
+
+    fn june(day: u32) -> NaiveDate {
+        at(day, 0, 0).date_naive()
+    }
+
+    #[test]
+    fn anomalies_need_fourteen_earlier_active_days() {
+        // A 100x spike on the 14th active day has only 13 days behind it
+        let mut inputs: Vec<_> = (1..=13).map(|day| (day, 1_000)).collect();
+        inputs.push((14, 100_000));
+        assert!(days(&inputs).cost_anomalies().is_empty());
+
+        // The first day in the data is never flagged, however costly
+        assert!(days(&[(1, 1_000_000)]).cost_anomalies().is_empty());
+    }
+
+    #[test]
+    fn flat_history_flags_any_increase() {
+        // With no variation the threshold is the mean itself
+        let mut inputs: Vec<_> = (1..=14).map(|day| (day, 1_000)).collect();
+        inputs.push((15, 1_000));
+        inputs.push((16, 1_100));
+        assert_eq!(days(&inputs).cost_anomalies(), HashSet::from([june(16)]));
+    }
+
+    #[test]
+    fn anomalies_are_two_deviations_above_the_trailing_active_days() {
+        // Active every other day, alternating 1k and 3k tokens: mean 2k, one
+        // standard deviation 1k, so the threshold is 4k
+        let mut inputs: Vec<_> = (0..14).map(|i| (1 + 2 * i, if i % 2 == 0 { 1_000 } else { 3_000 })).collect();
+        inputs.push((29, 4_100));
+        assert_eq!(days(&inputs).cost_anomalies(), HashSet::from([june(29)]));
+
+        inputs.pop();
+        inputs.push((29, 3_900));
+        assert!(days(&inputs).cost_anomalies().is_empty());
+    }
+
+    #[test]
+    fn the_window_slides_past_old_spikes() {
+        // Day 1's spike is in day 15's history but has left day 16's
+        let mut inputs = vec![(1, 50_000)];
+        inputs.extend((2..=14).map(|day| (day, 1_000)));
+        inputs.push((15, 1_500));
+        inputs.push((16, 1_500));
+        assert_eq!(days(&inputs).cost_anomalies(), HashSet::from([june(16)]));
+    }
+
+    #[test]
+    fn token_arithmetic_saturates_at_u64_max() {
+        let huge = TokenUsage {
+            input_tokens: u64::MAX,
+            output_tokens: u64::MAX,
+            cache_creation_input_tokens: u64::MAX,
+            cache_read_input_tokens: u64::MAX,
+        };
+        let mut total = huge.clone();
+        total.add(&huge);
+
+        assert_eq!(total.input_tokens, u64::MAX);
+        assert_eq!(total.output_tokens, u64::MAX);
+        assert_eq!(total.cache_creation_input_tokens, u64::MAX);
+        assert_eq!(total.cache_read_input_tokens, u64::MAX);
+        assert_eq!(total.total_input(), u64::MAX);
+        assert_eq!(total.total(), u64::MAX);
+    }
+}
@@ -0,0 +1,320 @@
+//! Minimal `{{...}}` templates for `--template`, rendered against usage stats
+//! serialized to JSON.
+//!
+//! Supported tags: `{{path.to.value}}` (array items by index, e.g.
+//! `daily.0.date`), `{{this}}` and `{{@index}}` inside loops, `{{@root.path}}`,
+//! `{{#each path}}...{{/each}}` and `{{#if path}}...{{else}}...{{/if}}`.
+//! Values are inserted as-is, without HTML or JSON escaping.
+
+use anyhow::{bail, Result};
+use serde_json::Value;
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Value(String),
+    Each { path: String, body: Vec<Node> },
+    If { path: String, then: Vec<Node>, otherwise: Vec<Node> },
+}
+
+#[derive(PartialEq)]
+enum BlockKind {
+    Each,
+    If,
+}
+
+impl BlockKind {
+    fn name(&self) -> &'static str {
+        match self {
+            BlockKind::Each => "each",
+            BlockKind::If => "if",
+        }
+    }
+}
+
+/// A block whose closing tag hasn't been reached yet
+struct OpenBlock {
+    kind: BlockKind,
+    path: String,
+    line: usize,
+    body: Vec<Node>,
+    otherwise: Option<Vec<Node>>,
+}
+
+impl OpenBlock {
+    fn nodes(&mut self) -> &mut Vec<Node> {
+        match self.otherwise {
+            Some(ref mut otherwise) => otherwise,
+            None => &mut self.body,
+        }
+    }
+
+    fn close(self) -> Node {
+        match self.kind {
+            BlockKind::Each => Node::Each { path: self.path, body: self.body },
+            BlockKind::If => Node::If {
+                path: self.path,
+                then: self.body,
+                otherwise: self.otherwise.unwrap_or_default(),
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    /// Parse template source; syntax errors name the line they're on
+    pub fn parse(source: &str) -> Result<Self> {
+        let line_at = |offset: usize| source[..offset].matches('\n').count() + 1;
+
+        let mut root = Vec::new();
+        let mut open: Vec<OpenBlock> = Vec::new();
+        let mut rest = source;
+
+        while let Some(start) = rest.find("{{") {
+            let offset = source.len() - rest.len() + start;
+            let line = line_at(offset);
+            if start > 0 {
+                nodes_for(&mut open, &mut root).push(Node::Text(rest[..start].to_string()));
+            }
+
+            let Some(end) = rest[start..].find("}}") else {
+                bail!("line {}: `{{{{` is never closed with `}}}}`", line);
+            };
+            let tag = rest[start + 2..start + end].trim();
+            rest = &rest[start + end + 2..];
+
+            if let Some(opening) = tag.strip_prefix('#') {
+                let (name, path) = opening.split_once(char::is_whitespace).unwrap_or((opening, ""));
+                let kind = match name {
+                    "each" => BlockKind::Each,
+                    "if" => BlockKind::If,
+                    _ => bail!("line {}: unknown block `{{{{#{}}}}}`; expected #each or #if", line, name),
+                };
+                let path = path.trim();
+                if path.is_empty() {
+                    bail!("line {}: `{{{{#{}}}}}` needs a value to {}", line, name,
+                        if kind == BlockKind::Each { "loop over" } else { "test" });
+                }
+                open.push(OpenBlock {
+                    kind,
+                    path: path.to_string(),
+                    line,
+                    body: Vec::new(),
+                    otherwise: None,
+                });
+            } else if let Some(name) = tag.strip_prefix('/') {
+                let Some(block) = open.pop() else {
+                    bail!("line {}: `{{{{/{}}}}}` has no matching opening tag", line, name.trim());
+                };
+                if block.kind.name() != name.trim() {
+                    bail!(
+                        "line {}: `{{{{/{}}}}}` closes `{{{{#{} {}}}}}` opened on line {}",
+                        line, name.trim(), block.kind.name(), block.path, block.line
+                    );
+                }
+                nodes_for(&mut open, &mut root).push(block.close());
+            } else if tag == "else" {
+                match open.last_mut() {
+                    Some(block) if block.kind == BlockKind::If && block.otherwise.is_none() => {
+                        block.otherwise = Some(Vec::new());
+                    }
+                    _ => bail!("line {}: `{{{{else}}}}` outside an `{{{{#if}}}}` block", line),
+                }
+            } else if tag.is_empty() || tag.contains(char::is_whitespace) {
+                bail!("line {}: expected a value path like `total_cost`, found `{{{{{}}}}}`", line, tag);
+            } else {
+                nodes_for(&mut open, &mut root).push(Node::Value(tag.to_string()));
+            }
+        }
+
+        if !rest.is_empty() {
+            nodes_for(&mut open, &mut root).push(Node::Text(rest.to_string()));
+        }
+        if let Some(block) = open.last() {
+            bail!(
+                "line {}: `{{{{#{} {}}}}}` is never closed with `{{{{/{}}}}}`",
+                block.line, block.kind.name(), block.path, block.kind.name()
+            );
+        }
+
+        Ok(Self { nodes: root })
+    }
+
+    pub fn render(&self, context: &Value) -> String {
+        let mut out = String::new();
+        render_nodes(&self.nodes, &mut vec![Scope { value: context, index: None }], &mut out);
+        out
+    }
+}
+
+fn nodes_for<'a>(open: &'a mut [OpenBlock], root: &'a mut Vec<Node>) -> &'a mut Vec<Node> {
+    match open.last_mut() {
+        Some(block) => block.nodes(),
+        None => root,
+    }
+}
+
+/// One level of `{{#each}}` nesting; the outermost scope is the whole context
+struct Scope<'a> {
+    value: &'a Value,
+    index: Option<usize>,
+}
+
+fn render_nodes<'a>(nodes: &[Node], scopes: &mut Vec<Scope<'a>>, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Value(path) if path == "@index" => {
+                if let Some(index) = scopes.last().and_then(|scope| scope.index) {
+                    out.push_str(&index.to_string());
+                }
+            }
+            Node::Value(path) => match lookup(path, scopes) {
+                Some(Value::String(s)) => out.push_str(s),
+                Some(Value::Null) | None => {}
+                Some(value) => out.push_str(&value.to_string()),
+            },
+            Node::Each { path, body } => {
+                let Some(Value::Array(items)) = lookup(path, scopes) else {
+                    continue;
+                };
+                for (index, item) in items.iter().enumerate() {
+                    scopes.push(Scope { value: item, index: Some(index) });
+                    render_nodes(body, scopes, out);
+                    scopes.pop();
+                }
+            }
+            Node::If { path, then, otherwise } => {
+                let branch = if lookup(path, scopes).is_some_and(truthy) { then } else { otherwise };
+                render_nodes(branch, scopes, out);
+            }
+        }
+    }
+}
+
+/// Resolve a dotted path against the innermost scope that has its first segment
+fn lookup<'a>(path: &str, scopes: &[Scope<'a>]) -> Option<&'a Value> {
+    let current = scopes.last()?;
+    if path == "this" || path == "." {
+        return Some(current.value);
+    }
+
+    let (candidates, path) = if let Some(path) = path.strip_prefix("@root.") {
+        (&scopes[..1], path)
+    } else if let Some(path) = path.strip_prefix("this.") {
+        (&scopes[scopes.len() - 1..], path)
+    } else {
+        (scopes, path)
+    };
+
+    let mut segments = path.split('.');
+    let first = segments.next()?;
+    let mut value = candidates.iter().rev().find_map(|scope| child(scope.value, first))?;
+    for segment in segments {
+        value = child(value, segment)?;
+    }
+    Some(value)
+}
+
+fn child<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    match value {
+        Value::Object(object) => object.get(key),
+        Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+        _ => None,
+    }
+}
+
+/// Falsy: null, false, zero, and empty strings, arrays and objects
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|f| f != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(object) => !object.is_empty(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn render(source: &str, context: &Value) -> String {
+        Template::parse(source).unwrap().render(context)
+    }
+
+    fn parse_error(source: &str) -> String {
+        Template::parse(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn renders_value_paths_and_array_indexes() {
+        let context = json!({
+            "total_cost": 1.5,
+            "today": { "date": "2025-06-01" },
+            "daily": [{ "date": "2025-05-31" }, { "date": "2025-06-01" }],
+            "missing": null
+        });
+
+        assert_eq!(render("${{total_cost}} on {{today.date}}", &context), "$1.5 on 2025-06-01");
+        assert_eq!(render("{{daily.1.date}}", &context), "2025-06-01");
+        assert_eq!(render("[{{missing}}{{nope.at.all}}{{daily.9.date}}]", &context), "[]");
+    }
+
+    #[test]
+    fn nested_each_sees_index_and_root() {
+        let context = json!({
+            "currency": "USD",
+            "months": [
+                { "month": "2025-05", "days": [{ "cost": 1 }, { "cost": 2 }] },
+                { "month": "2025-06", "days": [{ "cost": 3 }] }
+            ]
+        });
+        let source = "{{#each months}}{{month}}:{{#each days}} {{@index}}={{cost}}{{@root.currency}}{{/each}};{{/each}}";
+
+        assert_eq!(render(source, &context), "2025-05: 0=1USD 1=2USD;2025-06: 0=3USD;");
+        assert_eq!(render("{{#each tags}}{{@index}}{{this}} {{/each}}", &json!({ "tags": ["a", "b"] })), "0a 1b ");
+    }
+
+    #[test]
+    fn if_treats_empty_and_zero_values_as_false() {
+        let source = "{{#if value}}yes{{else}}no{{/if}}";
+        let falsy = [json!(null), json!(false), json!(0), json!(0.0), json!(""), json!([]), json!({})];
+        let truthy = [json!(true), json!(1), json!(-0.5), json!("x"), json!([0]), json!({ "a": null })];
+
+        for value in falsy {
+            assert_eq!(render(source, &json!({ "value": value })), "no", "{}", value);
+        }
+        for value in truthy {
+            assert_eq!(render(source, &json!({ "value": value })), "yes", "{}", value);
+        }
+        assert_eq!(render(source, &json!({})), "no");
+        assert_eq!(render("{{#if value}}yes{{/if}}", &json!({})), "");
+    }
+
+    #[test]
+    fn syntax_errors_name_their_line() {
+        assert_eq!(parse_error("ok\n{{total_cost"), "line 2: `{{` is never closed with `}}`");
+        assert_eq!(
+            parse_error("{{#each daily}}\n{{date}}\n{{/if}}"),
+            "line 3: `{{/if}}` closes `{{#each daily}}` opened on line 1"
+        );
+        assert_eq!(parse_error("a\nb\n{{else}}"), "line 3: `{{else}}` outside an `{{#if}}` block");
+        assert_eq!(
+            parse_error("{{#if a}}{{else}}{{else}}{{/if}}"),
+            "line 1: `{{else}}` outside an `{{#if}}` block"
+        );
+        assert_eq!(
+            parse_error("x\n{{#if total_cost}}\nyes"),
+            "line 2: `{{#if total_cost}}` is never closed with `{{/if}}`"
+        );
+        assert_eq!(parse_error("{{/each}}"), "line 1: `{{/each}}` has no matching opening tag");
+    }
+}
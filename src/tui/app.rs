@@ -1,28 +1,138 @@
-use chrono::{Local, Duration, Datelike};
-use crate::models::{UsageStats, DailyUsage, TokenUsage};
+use crate::cli::{Order, Unit};
+use crate::currency::Currency;
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDate;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use crate::models::{UsageStats, DailyUsage, FamilyUsage, ModelUsage, MonthlyUsage, SessionUsage, TokenUsage};
+use crate::tui::state;
+use crate::util::token_total;
+
+/// Maximum number of rows shown in the sessions list
+const SESSION_LIST_LIMIT: usize = 20;
+
+/// How long a first `q` press waits for the confirming second press
+const QUIT_CONFIRM_WINDOW: Duration = Duration::from_secs(1);
+
+/// Default daily cost in USD above which list costs turn yellow
+pub const DEFAULT_COST_WARN: f64 = 5.0;
+
+/// Default daily cost in USD above which list costs turn red
+pub const DEFAULT_COST_HIGH: f64 = 20.0;
 
 pub struct App {
+    /// Stats being displayed: everything, or `all_stats` limited to `date_range`
     pub stats: UsageStats,
+    all_stats: UsageStats,
+    /// Inclusive range every tab is limited to; `None` shows all history
+    pub date_range: Option<(NaiveDate, NaiveDate)>,
+    /// The date-range overlay, while it's open
+    pub range_input: Option<RangeInput>,
     pub selected_tab: Tab,
     pub selected_index: usize,
     pub should_quit: bool,
+    pub unit: Unit,
+    pub order: Order,
+    pub color: bool,
+    pub currency: Currency,
+    /// True while the user is typing into the search line
+    pub search_active: bool,
+    /// Query filtering the active list; empty means no filter
+    pub search_query: String,
+    /// One-off message shown in the footer until the next key press
+    pub status_message: Option<String>,
+    /// Directories scanned for transcripts, shown when there's no data
+    pub data_dirs: Vec<String>,
+    /// Show the untruncated project path for the highlighted session
+    pub show_full_path: bool,
+    /// Days whose cost is unusually high against recent history
+    pub anomalies: HashSet<NaiveDate>,
+    /// Limit the daily list to anomalous days
+    pub anomalies_only: bool,
+    /// Show only the project's directory name instead of its path
+    pub basename_only: bool,
+    /// Prefix stripped from displayed project paths
+    pub projects_root: Option<PathBuf>,
+    /// Show session activity as "5m ago" instead of an absolute timestamp
+    pub relative_time: bool,
+    /// Break daily token totals out into input, output, cache write and cache read
+    pub detailed_tokens: bool,
+    /// Show each month's daily cost trend on the Monthly tab
+    pub monthly_spark: bool,
+    /// List model families on the Models tab instead of exact ids
+    pub by_family: bool,
+    families: Vec<FamilyUsage>,
+    /// Require a second `q` press before quitting
+    pub confirm_quit: bool,
+    quit_requested_at: Option<Instant>,
+    /// Chart the costliest projects on the Overview tab
+    pub top_projects: bool,
+    /// Daily cost in USD above which the Daily and Monthly lists show cost in yellow
+    pub cost_warn: f64,
+    /// Daily cost in USD above which they show it in red
+    pub cost_high: f64,
+}
+
+/// Contents of the date-range overlay's two `YYYY-MM-DD` fields
+#[derive(Default)]
+pub struct RangeInput {
+    pub from: String,
+    pub to: String,
+    /// Typing goes to `to` rather than `from`
+    pub editing_to: bool,
+    /// Why the last confirmation was rejected
+    pub error: Option<String>,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Tab {
     Overview,
     Daily,
     Sessions,
     Monthly,
+    Models,
+}
+
+impl Tab {
+    /// Tabs in display order; number keys jump to them by position
+    pub const ALL: [Tab; 5] = [Tab::Overview, Tab::Daily, Tab::Sessions, Tab::Monthly, Tab::Models];
 }
 
 impl App {
-    pub fn new(stats: UsageStats) -> Self {
+    pub fn new(stats: UsageStats, unit: Unit, order: Order, color: bool, currency: Currency) -> Self {
         Self {
+            anomalies: stats.cost_anomalies(),
+            families: stats.models_by_family(),
+            anomalies_only: false,
+            all_stats: stats.clone(),
             stats,
-            selected_tab: Tab::Overview,
+            date_range: None,
+            range_input: None,
+            selected_tab: state::load_selected_tab().unwrap_or(Tab::Overview),
             selected_index: 0,
             should_quit: false,
+            unit,
+            order,
+            color,
+            currency,
+            search_active: false,
+            search_query: String::new(),
+            status_message: None,
+            data_dirs: Vec::new(),
+            show_full_path: false,
+            basename_only: false,
+            projects_root: None,
+            relative_time: false,
+            detailed_tokens: false,
+            monthly_spark: false,
+            by_family: false,
+            confirm_quit: true,
+            quit_requested_at: None,
+            top_projects: false,
+            cost_warn: DEFAULT_COST_WARN,
+            cost_high: DEFAULT_COST_HIGH,
         }
     }
     
@@ -31,27 +141,38 @@ impl App {
             Tab::Overview => Tab::Daily,
             Tab::Daily => Tab::Sessions,
             Tab::Sessions => Tab::Monthly,
-            Tab::Monthly => Tab::Overview,
+            Tab::Monthly => Tab::Models,
+            Tab::Models => Tab::Overview,
         };
         self.selected_index = 0;
+        self.clear_search();
     }
     
     pub fn previous_tab(&mut self) {
         self.selected_tab = match self.selected_tab {
-            Tab::Overview => Tab::Monthly,
+            Tab::Overview => Tab::Models,
             Tab::Daily => Tab::Overview,
             Tab::Sessions => Tab::Daily,
             Tab::Monthly => Tab::Sessions,
+            Tab::Models => Tab::Monthly,
         };
         self.selected_index = 0;
+        self.clear_search();
+    }
+    
+    pub fn select_tab(&mut self, tab: Tab) {
+        self.selected_tab = tab;
+        self.selected_index = 0;
+        self.clear_search();
     }
     
     pub fn next_item(&mut self) {
         let max_index = match self.selected_tab {
             Tab::Overview => 0,
-            Tab::Daily => self.stats.daily.len().saturating_sub(1),
-            Tab::Sessions => self.stats.sessions.len().saturating_sub(1),
-            Tab::Monthly => self.stats.monthly.len().saturating_sub(1),
+            Tab::Daily => self.daily_rows().len().saturating_sub(1),
+            Tab::Sessions => self.session_rows().len().saturating_sub(1),
+            Tab::Monthly => self.monthly_rows().len().saturating_sub(1),
+            Tab::Models => self.model_rows().len().saturating_sub(1),
         };
         
         if self.selected_index < max_index {
@@ -65,30 +186,211 @@ impl App {
         }
     }
     
+    pub fn start_search(&mut self) {
+        self.search_active = true;
+    }
+    
+    /// Keep the current query as a filter and return to navigation
+    pub fn confirm_search(&mut self) {
+        self.search_active = false;
+    }
+    
+    pub fn clear_search(&mut self) {
+        self.search_active = false;
+        self.search_query.clear();
+        self.selected_index = 0;
+    }
+    
+    pub fn push_search_char(&mut self, c: char) {
+        self.search_query.push(c);
+        self.selected_index = 0;
+    }
+    
+    pub fn pop_search_char(&mut self) {
+        self.search_query.pop();
+        self.selected_index = 0;
+    }
+    
+    fn matches_query(&self, value: &str) -> bool {
+        self.search_query.is_empty()
+            || value.to_lowercase().contains(&self.search_query.to_lowercase())
+    }
+    
+    /// Daily rows in display order, filtered by date substring
+    pub fn daily_rows(&self) -> Vec<&DailyUsage> {
+        let mut rows: Vec<_> = self.stats.daily.iter()
+            .filter(|d| self.matches_query(&d.date.format("%Y-%m-%d").to_string()))
+            .filter(|d| !self.anomalies_only || self.anomalies.contains(&d.date))
+            .collect();
+        match self.order {
+            Order::DateAsc => rows.sort_by_key(|d| d.date),
+            Order::DateDesc => rows.sort_by_key(|d| std::cmp::Reverse(d.date)),
+            Order::CostDesc => rows.sort_by(|a, b| b.total_cost.total_cmp(&a.total_cost)),
+            Order::TokensDesc => rows.sort_by_key(|d| std::cmp::Reverse(token_total(&d.tokens))),
+        }
+        rows
+    }
+    
+    /// Session rows in display order, filtered by project path. Filtering and
+    /// sorting happen before truncation so cost/token orderings show the top
+    /// sessions of all time.
+    pub fn session_rows(&self) -> Vec<&SessionUsage> {
+        let mut rows: Vec<_> = self.stats.sessions.iter()
+            .filter(|s| self.matches_query(&s.project_path))
+            .collect();
+        match self.order {
+            Order::DateAsc => rows.sort_by_key(|s| s.last_activity),
+            Order::DateDesc => rows.sort_by_key(|s| std::cmp::Reverse(s.last_activity)),
+            Order::CostDesc => rows.sort_by(|a, b| b.total_cost.total_cmp(&a.total_cost)),
+            Order::TokensDesc => rows.sort_by_key(|s| std::cmp::Reverse(token_total(&s.tokens))),
+        }
+        rows.truncate(SESSION_LIST_LIMIT);
+        rows
+    }
+    
+    /// Monthly rows, filtered by month
+    pub fn monthly_rows(&self) -> Vec<&MonthlyUsage> {
+        self.stats.monthly.iter()
+            .filter(|m| self.matches_query(&m.month))
+            .collect()
+    }
+    
+    /// Model rows by cost, filtered by model name
+    pub fn model_rows(&self) -> Vec<&ModelUsage> {
+        let models: Vec<&ModelUsage> = if self.by_family {
+            self.families.iter().map(|f| &f.usage).collect()
+        } else {
+            self.stats.models.iter().collect()
+        };
+        models.into_iter()
+            .filter(|m| self.matches_query(&m.model))
+            .collect()
+    }
+    
+    /// Project path as displayed: relative to `projects_root` when it's inside it
+    pub fn display_project<'a>(&self, path: &'a str) -> &'a str {
+        self.projects_root.as_deref()
+            .and_then(|root| Path::new(path).strip_prefix(root).ok())
+            .and_then(|relative| relative.to_str())
+            .filter(|relative| !relative.is_empty())
+            .unwrap_or(path)
+    }
+    
+    /// Open the date-range overlay, prefilled with the active range or the
+    /// span of all history
+    pub fn open_range_input(&mut self) {
+        let (from, to) = self.date_range.or_else(|| {
+            Some((self.all_stats.daily.first()?.date, self.all_stats.daily.last()?.date))
+        }).map_or_else(Default::default, |(from, to)| (from.to_string(), to.to_string()));
+        self.range_input = Some(RangeInput { from, to, ..RangeInput::default() });
+    }
+    
+    pub fn close_range_input(&mut self) {
+        self.range_input = None;
+    }
+    
+    pub fn switch_range_field(&mut self) {
+        if let Some(input) = &mut self.range_input {
+            input.editing_to = !input.editing_to;
+        }
+    }
+    
+    pub fn push_range_char(&mut self, c: char) {
+        if let Some(input) = &mut self.range_input {
+            if input.editing_to { input.to.push(c) } else { input.from.push(c) }
+        }
+    }
+    
+    pub fn pop_range_char(&mut self) {
+        if let Some(input) = &mut self.range_input {
+            if input.editing_to { input.to.pop() } else { input.from.pop() };
+        }
+    }
+    
+    /// Apply the overlay's range, or leave it open with an error if it doesn't parse
+    pub fn confirm_range_input(&mut self) {
+        let Some(input) = &mut self.range_input else {
+            return;
+        };
+        let parse = |field: &str, value: &str| {
+            NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+                .map_err(|_| format!("{} must be a YYYY-MM-DD date", field))
+        };
+        let range = parse("From", &input.from).and_then(|from| {
+            let to = parse("To", &input.to)?;
+            if from > to {
+                return Err("From must not be after To".to_string());
+            }
+            Ok((from, to))
+        });
+        match range {
+            Ok((from, to)) => {
+                self.range_input = None;
+                self.set_date_range(Some((from, to)));
+            }
+            Err(e) => input.error = Some(e),
+        }
+    }
+    
+    /// Limit every tab to `range`, or show all history again with `None`.
+    /// Slices are recomputed from the loaded stats without touching disk.
+    pub fn set_date_range(&mut self, range: Option<(NaiveDate, NaiveDate)>) {
+        self.stats = match range {
+            Some((from, to)) => self.all_stats.between(from, to),
+            None => self.all_stats.clone(),
+        };
+        self.families = self.stats.models_by_family();
+        self.date_range = range;
+        self.selected_index = 0;
+    }
+    
+    pub fn toggle_full_path(&mut self) {
+        self.show_full_path = !self.show_full_path;
+    }
+    
+    pub fn toggle_anomalies_only(&mut self) {
+        self.anomalies_only = !self.anomalies_only;
+        self.selected_index = 0;
+    }
+    
+    pub fn toggle_basename(&mut self) {
+        self.basename_only = !self.basename_only;
+    }
+    
+    pub fn toggle_relative_time(&mut self) {
+        self.relative_time = !self.relative_time;
+    }
+    
+    pub fn toggle_detailed_tokens(&mut self) {
+        self.detailed_tokens = !self.detailed_tokens;
+    }
+    
     pub fn quit(&mut self) {
         self.should_quit = true;
     }
     
+    /// Quit on a second `q` within the confirmation window, or immediately
+    /// when confirmation is turned off
+    pub fn request_quit(&mut self) {
+        let confirmed = self.quit_requested_at
+            .is_some_and(|at| at.elapsed() <= QUIT_CONFIRM_WINDOW);
+        if !self.confirm_quit || confirmed {
+            self.quit();
+        } else {
+            self.quit_requested_at = Some(Instant::now());
+            self.status_message = Some("Press q again to quit".to_string());
+        }
+    }
+    
     pub fn get_today_stats(&self) -> Option<&DailyUsage> {
-        let today = Local::now().date_naive();
-        self.stats.daily.iter().find(|d| d.date == today)
+        self.stats.get_today_stats()
     }
     
     pub fn get_week_stats(&self) -> (TokenUsage, f64) {
-        let today = Local::now().date_naive();
-        let week_ago = today - Duration::days(7);
-        
-        self.stats.daily.iter()
-            .filter(|d| d.date > week_ago)
-            .fold((TokenUsage::default(), 0.0), |(mut tokens, cost), d| {
-                tokens.add(&d.tokens);
-                (tokens, cost + d.total_cost)
-            })
-    }
-    
-    pub fn get_month_stats(&self) -> Option<&crate::models::MonthlyUsage> {
-        let today = Local::now().date_naive();
-        let current_month = format!("{:04}-{:02}", today.year(), today.month());
-        self.stats.monthly.iter().find(|m| m.month == current_month)
+        self.stats.get_week_stats()
+    }
+    
+    pub fn get_month_stats(&self) -> Option<&MonthlyUsage> {
+        self.stats.get_month_stats()
     }
 }
\ No newline at end of file
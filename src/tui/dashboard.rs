@@ -1,6 +1,8 @@
 use anyhow::Result;
+use chrono::{DateTime, Datelike, Local, Utc};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    cursor::Show,
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -8,17 +10,81 @@ use ratatui::{
     backend::CrosstermBackend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
+    symbols,
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Sparkline, Tabs},
+    widgets::{Bar, BarChart, BarGroup, Block, Borders, Clear, List, ListItem, Paragraph, Sparkline, Tabs, Wrap},
     Frame, Terminal,
 };
 use std::io;
+use tracing::warn;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-use crate::tui::app::{App, Tab};
+use crate::cli::Unit;
+use crate::models::{DailyUsage, HourlyUsage, MonthlyUsage, TokenUsage};
+use crate::tui::app::{App, RangeInput, Tab};
+use crate::tui::export::export_current_view;
+use crate::tui::state;
+use crate::util::{ascii, excludes_cache_read, format_number, glyph, sparkline, token_total};
+
+/// Below this size the layout can't fit, so only a warning is drawn
+const MIN_WIDTH: u16 = 40;
+const MIN_HEIGHT: u16 = 15;
+
+/// Narrower than this, the overview stat cards stack instead of sitting four across
+const CARDS_ACROSS_MIN_WIDTH: u16 = 80;
+
+/// Overview token mix block: bordered bar row plus legend row
+const TOKEN_MIX_HEIGHT: u16 = 4;
+
+/// Bars in the overview's `--top-projects` chart
+const TOP_PROJECTS: usize = 5;
+
+/// Widest project label in that chart, in columns
+const TOP_PROJECT_LABEL_WIDTH: usize = 24;
+
+/// Puts the terminal back to normal when dropped, so an early `?` return or a
+/// panic unwinding out of the loop doesn't leave it in raw mode
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+/// Leave raw mode and the alternate screen; safe to call more than once
+fn restore_terminal() {
+    let _ = disable_raw_mode();
+    let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, Show);
+}
+
+/// Restore the terminal before anything else can kill the process: the panic
+/// hook runs ahead of the default one so the message prints on the normal
+/// screen, and a SIGINT from outside (raw mode swallows Ctrl-C as a key)
+/// exits with the conventional 130
+fn install_restore_hooks() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore_terminal();
+        default_hook(info);
+    }));
+    
+    if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+        runtime.spawn(async {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                restore_terminal();
+                std::process::exit(130);
+            }
+        });
+    }
+}
 
 pub fn run_dashboard(app: App) -> Result<()> {
+    install_restore_hooks();
+    
     // Setup terminal
     enable_raw_mode()?;
+    let guard = TerminalGuard;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
@@ -31,9 +97,61 @@ pub fn run_dashboard(app: App) -> Result<()> {
         terminal.draw(|f| draw_ui(f, &mut app))?;
         
         if let Event::Key(key) = event::read()? {
+            app.status_message = None;
+            
+            // Raw mode delivers Ctrl-C as a key press rather than a signal
+            if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                app.quit();
+                break;
+            }
+            
+            if app.range_input.is_some() {
+                match key.code {
+                    KeyCode::Char(c) => app.push_range_char(c),
+                    KeyCode::Backspace => app.pop_range_char(),
+                    KeyCode::Tab | KeyCode::BackTab => app.switch_range_field(),
+                    KeyCode::Enter => app.confirm_range_input(),
+                    KeyCode::Esc => app.close_range_input(),
+                    _ => {}
+                }
+                continue;
+            }
+            
+            if app.search_active {
+                match key.code {
+                    KeyCode::Char(c) => app.push_search_char(c),
+                    KeyCode::Backspace => app.pop_search_char(),
+                    KeyCode::Enter => app.confirm_search(),
+                    KeyCode::Esc => app.clear_search(),
+                    _ => {}
+                }
+                continue;
+            }
+            
             match key.code {
-                KeyCode::Char('q') => app.quit(),
+                KeyCode::Char('q') => app.request_quit(),
+                KeyCode::Char('/') => app.start_search(),
+                KeyCode::Char('f') => app.toggle_full_path(),
+                KeyCode::Char('r') => app.toggle_relative_time(),
+                KeyCode::Char('b') => app.toggle_basename(),
+                KeyCode::Char('a') => app.toggle_anomalies_only(),
+                KeyCode::Char('d') => app.toggle_detailed_tokens(),
+                KeyCode::Char('D') => app.open_range_input(),
+                KeyCode::Char('e') => {
+                    app.status_message = Some(match export_current_view(&app) {
+                        Ok(path) => format!("Exported to {}", path.display()),
+                        Err(e) => format!("Export failed: {}", e),
+                    });
+                }
+                // Esc peels back one filter at a time: the search, then the date range
+                KeyCode::Esc if app.search_query.is_empty() => app.set_date_range(None),
+                KeyCode::Esc => app.clear_search(),
                 KeyCode::Tab => app.next_tab(),
+                KeyCode::Char(c @ '1'..='9') => {
+                    if let Some(&tab) = Tab::ALL.get(c as usize - '1' as usize) {
+                        app.select_tab(tab);
+                    }
+                }
                 KeyCode::BackTab => app.previous_tab(),
                 KeyCode::Down | KeyCode::Char('j') => app.next_item(),
                 KeyCode::Up | KeyCode::Char('k') => app.previous_item(),
@@ -47,18 +165,28 @@ pub fn run_dashboard(app: App) -> Result<()> {
     }
     
     // Restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    drop(guard);
+    
+    if let Err(e) = state::save_selected_tab(app.selected_tab) {
+        warn!("Failed to save dashboard state: {:#}", e);
+    }
     
     Ok(())
 }
 
 fn draw_ui(f: &mut Frame, app: &mut App) {
+    let area = f.area();
+    if area.width < MIN_WIDTH || area.height < MIN_HEIGHT {
+        let message = Paragraph::new(format!(
+            "Terminal too small ({}x{}), need at least {}x{}",
+            area.width, area.height, MIN_WIDTH, MIN_HEIGHT
+        ))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+        f.render_widget(message, area);
+        return;
+    }
+    
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -72,28 +200,58 @@ fn draw_ui(f: &mut Frame, app: &mut App) {
     draw_header(f, app, chunks[0]);
     
     // Draw main content based on selected tab
-    match app.selected_tab {
-        Tab::Overview => draw_overview(f, app, chunks[1]),
-        Tab::Daily => draw_daily(f, app, chunks[1]),
-        Tab::Sessions => draw_sessions(f, app, chunks[1]),
-        Tab::Monthly => draw_monthly(f, app, chunks[1]),
+    if app.stats.is_empty() {
+        draw_empty_state(f, app, chunks[1]);
+    } else {
+        match app.selected_tab {
+            Tab::Overview => draw_overview(f, app, chunks[1]),
+            Tab::Daily => draw_daily(f, app, chunks[1]),
+            Tab::Sessions => draw_sessions(f, app, chunks[1]),
+            Tab::Monthly => draw_monthly(f, app, chunks[1]),
+            Tab::Models => draw_models(f, app, chunks[1]),
+        }
     }
     
     // Draw footer
-    draw_footer(f, chunks[2]);
+    draw_footer(f, app, chunks[2]);
+    
+    if let Some(input) = &app.range_input {
+        draw_range_input(f, input, f.area());
+    }
+    
+    // Strip colors after drawing so every widget respects NO_COLOR/--color never
+    if !app.color {
+        for cell in f.buffer_mut().content.iter_mut() {
+            cell.set_fg(Color::Reset).set_bg(Color::Reset);
+        }
+    }
 }
 
 fn draw_header(f: &mut Frame, app: &App, area: Rect) {
-    let titles = vec!["Overview", "Daily", "Sessions", "Monthly"];
+    let titles = vec!["Overview", "Daily", "Sessions", "Monthly", "Models"];
     let selected = match app.selected_tab {
         Tab::Overview => 0,
         Tab::Daily => 1,
         Tab::Sessions => 2,
         Tab::Monthly => 3,
+        Tab::Models => 4,
+    };
+    
+    let mut title = if app.currency.is_usd() {
+        " Claude Code Monitor ".to_string()
+    } else {
+        format!(" Claude Code Monitor ({}) ", app.currency.code)
     };
+    if excludes_cache_read() {
+        title.push_str("- tokens excl. cache reads ");
+    }
+    if let Some((from, to)) = app.date_range {
+        title.push_str(&format!("- {} to {} ", from, to));
+    }
     
     let tabs = Tabs::new(titles)
-        .block(Block::default().borders(Borders::ALL).title(" Claude Code Monitor "))
+        .block(bordered().title(title))
+        .divider(glyph(symbols::line::VERTICAL, "|"))
         .select(selected)
         .style(Style::default().fg(Color::White))
         .highlight_style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD));
@@ -101,49 +259,139 @@ fn draw_header(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(tabs, area);
 }
 
-fn draw_footer(f: &mut Frame, area: Rect) {
+fn draw_footer(f: &mut Frame, app: &App, area: Rect) {
+    if let Some(message) = &app.status_message {
+        let status = Paragraph::new(message.as_str())
+            .block(bordered())
+            .alignment(Alignment::Center);
+        
+        f.render_widget(status, area);
+        return;
+    }
+    
+    if app.search_active || !app.search_query.is_empty() {
+        let hint = if app.search_active {
+            "  (Enter to keep, Esc to clear)"
+        } else {
+            "  (/ to edit, Esc to clear)"
+        };
+        let search = Paragraph::new(Line::from(vec![
+            Span::styled("/", Style::default().fg(Color::Cyan)),
+            Span::raw(app.search_query.clone()),
+            Span::styled(hint, Style::default().fg(Color::DarkGray)),
+        ]))
+        .block(bordered().title(" Filter "));
+        
+        f.render_widget(search, area);
+        return;
+    }
+    
     let footer = Paragraph::new(Line::from(vec![
         Span::raw("Press "),
         Span::styled("Tab", Style::default().fg(Color::Cyan)),
+        Span::raw("/"),
+        Span::styled(format!("1-{}", Tab::ALL.len()), Style::default().fg(Color::Cyan)),
         Span::raw(" to switch tabs, "),
-        Span::styled("↑↓", Style::default().fg(Color::Cyan)),
+        Span::styled(glyph("↑↓", "Up/Down"), Style::default().fg(Color::Cyan)),
         Span::raw(" to navigate, "),
+        Span::styled("/", Style::default().fg(Color::Cyan)),
+        Span::raw(" to filter, "),
+        Span::styled("e", Style::default().fg(Color::Cyan)),
+        Span::raw(" to export CSV, "),
         Span::styled("q", Style::default().fg(Color::Cyan)),
         Span::raw(" to quit"),
     ]))
-    .block(Block::default().borders(Borders::ALL))
+    .block(bordered())
     .alignment(Alignment::Center);
     
     f.render_widget(footer, area);
 }
 
-fn draw_overview(f: &mut Frame, app: &App, area: Rect) {
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(8),  // Stats cards
-            Constraint::Min(0),     // Chart
-        ])
-        .split(area);
+/// Centered overlay with the From and To fields; the one being edited is highlighted
+fn draw_range_input(f: &mut Frame, input: &RangeInput, area: Rect) {
+    let width = 44.min(area.width);
+    let height = 6.min(area.height);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
     
-    // Draw stats cards
-    let stats_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage(25),
-            Constraint::Percentage(25),
-            Constraint::Percentage(25),
-            Constraint::Percentage(25),
+    let field = |label: &str, value: &str, active: bool| {
+        let style = if active {
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        let cursor = if active { "_" } else { "" };
+        Line::from(vec![
+            Span::raw(format!("{:>6} ", label)),
+            Span::styled(format!("{}{}", value, cursor), style),
         ])
-        .split(chunks[0]);
+    };
+    let status = match &input.error {
+        Some(error) => Line::from(Span::styled(error.clone(), Style::default().fg(Color::Red))),
+        None => Line::from(Span::styled(
+            "Tab: next field  Enter: apply  Esc: cancel",
+            Style::default().fg(Color::DarkGray),
+        )),
+    };
+    let text = vec![
+        field("From", &input.from, !input.editing_to),
+        field("To", &input.to, input.editing_to),
+        Line::from(""),
+        status,
+    ];
     
+    let widget = Paragraph::new(text)
+        .block(bordered().title(" Date Range (YYYY-MM-DD) "));
+    f.render_widget(Clear, popup);
+    f.render_widget(widget, popup);
+}
+
+fn draw_empty_state(f: &mut Frame, app: &App, area: Rect) {
+    if let Some((from, to)) = app.date_range {
+        let text = vec![
+            Line::from(""),
+            Line::from(Span::styled(
+                format!("No usage between {} and {}", from, to),
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+            Line::from("Press D to change the range or Esc to show all history."),
+        ];
+        let panel = Paragraph::new(text)
+            .block(bordered())
+            .alignment(Alignment::Center);
+        f.render_widget(panel, area);
+        return;
+    }
+    
+    let mut text = vec![
+        Line::from(""),
+        Line::from(Span::styled("No usage data found", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
+        Line::from(""),
+        Line::from("Searched:"),
+    ];
+    text.extend(app.data_dirs.iter().map(|dir| Line::from(dir.as_str())));
+    text.push(Line::from(""));
+    text.push(Line::from("Set CLAUDE_CONFIG_DIR to the Claude directory holding your transcripts."));
+    
+    let panel = Paragraph::new(text)
+        .block(bordered())
+        .alignment(Alignment::Center);
+    f.render_widget(panel, area);
+}
+
+fn draw_overview(f: &mut Frame, app: &App, area: Rect) {
     // Today's stats
     let today_stats = app.get_today_stats();
     let today_text = if let Some(stats) = today_stats {
         vec![
             Line::from(Span::styled("Today", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))),
-            Line::from(format!("Tokens: {}", format_number(stats.tokens.total()))),
-            Line::from(format!("Cost: ${:.2}", stats.total_cost)),
+            Line::from(format!("Tokens: {}", format_number(token_total(&stats.tokens)))),
+            Line::from(format!("Cost: {}", app.currency.format(stats.total_cost, 2))),
         ]
     } else {
         vec![
@@ -152,31 +400,25 @@ fn draw_overview(f: &mut Frame, app: &App, area: Rect) {
         ]
     };
     
-    let today_widget = Paragraph::new(today_text)
-        .block(Block::default().borders(Borders::ALL))
-        .alignment(Alignment::Center);
-    f.render_widget(today_widget, stats_chunks[0]);
-    
     // Week stats
     let (week_tokens, week_cost) = app.get_week_stats();
     let week_text = vec![
         Line::from(Span::styled("Last 7 Days", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD))),
-        Line::from(format!("Tokens: {}", format_number(week_tokens.total()))),
-        Line::from(format!("Cost: ${:.2}", week_cost)),
+        Line::from(format!("Tokens: {}", format_number(token_total(&week_tokens)))),
+        Line::from(format!("Cost: {}", app.currency.format(week_cost, 2))),
     ];
     
-    let week_widget = Paragraph::new(week_text)
-        .block(Block::default().borders(Borders::ALL))
-        .alignment(Alignment::Center);
-    f.render_widget(week_widget, stats_chunks[1]);
-    
     // Month stats
     let month_stats = app.get_month_stats();
     let month_text = if let Some(stats) = month_stats {
         vec![
             Line::from(Span::styled("This Month", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
-            Line::from(format!("Tokens: {}", format_number(stats.tokens.total()))),
-            Line::from(format!("Cost: ${:.2}", stats.total_cost)),
+            Line::from(format!("Tokens: {}", format_number(token_total(&stats.tokens)))),
+            Line::from(format!("Cost: {}", app.currency.format(stats.total_cost, 2))),
+            Line::from(match stats.projected_cost(Local::now().date_naive()) {
+                Some(projected) => format!("Projected: {}", app.currency.format(projected, 2)),
+                None => "Projected: insufficient data".to_string(),
+            }),
         ]
     } else {
         vec![
@@ -185,71 +427,282 @@ fn draw_overview(f: &mut Frame, app: &App, area: Rect) {
         ]
     };
     
-    let month_widget = Paragraph::new(month_text)
-        .block(Block::default().borders(Borders::ALL))
-        .alignment(Alignment::Center);
-    f.render_widget(month_widget, stats_chunks[2]);
-    
     // All-time stats
     let total_text = vec![
-        Line::from(Span::styled("All Time", Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD))),
-        Line::from(format!("Tokens: {}", format_number(app.stats.total_tokens.total()))),
-        Line::from(format!("Cost: ${:.2}", app.stats.total_cost)),
+        Line::from(Span::styled(
+            if app.date_range.is_some() { "Selected Range" } else { "All Time" },
+            Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(format!("Tokens: {}", format_number(token_total(&app.stats.total_tokens)))),
+        Line::from(format!("Cost: {}", app.currency.format(app.stats.total_cost, 2))),
         Line::from(format!("Sessions: {}", app.stats.sessions.len())),
+        Line::from(format!("Avg/session: {}", app.currency.format(app.stats.averages().cost_per_session, 2))),
     ];
     
-    let total_widget = Paragraph::new(total_text)
-        .block(Block::default().borders(Borders::ALL))
-        .alignment(Alignment::Center);
-    f.render_widget(total_widget, stats_chunks[3]);
+    let cards = [today_text, week_text, month_text, total_text];
+    
+    // Four cards across, or one per row on narrow terminals
+    let (card_areas, chart_area) = if area.width >= CARDS_ACROSS_MIN_WIDTH {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(8),  // Stats cards
+                Constraint::Min(0),     // Chart
+            ])
+            .split(area);
+        
+        let stats_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(25),
+                Constraint::Percentage(25),
+                Constraint::Percentage(25),
+                Constraint::Percentage(25),
+            ])
+            .split(chunks[0]);
+        (stats_chunks.to_vec(), chunks[1])
+    } else {
+        // Only stack as many cards as fit whole; squashed borders are unreadable
+        let mut used = 0;
+        let mut constraints: Vec<_> = cards.iter()
+            .map(|lines| lines.len() as u16 + 2)
+            .take_while(|height| {
+                used += height;
+                used <= area.height
+            })
+            .map(Constraint::Length)
+            .collect();
+        let shown = constraints.len();
+        constraints.push(Constraint::Min(0));
+        
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(area);
+        (chunks[..shown].to_vec(), chunks[shown])
+    };
     
-    // Draw sparkline chart
-    let daily_costs: Vec<u64> = app.stats.daily.iter()
+    for (lines, card_area) in cards.into_iter().zip(card_areas) {
+        let widget = Paragraph::new(lines)
+            .block(bordered())
+            .alignment(Alignment::Center);
+        f.render_widget(widget, card_area);
+    }
+    
+    // Stacked cards can use up the whole tab; skip charts with no room to plot
+    if chart_area.height < 3 {
+        return;
+    }
+    
+    // All-time token mix, when there's still room for the charts below it
+    let chart_area = if chart_area.height >= TOKEN_MIX_HEIGHT + 3 {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(TOKEN_MIX_HEIGHT), Constraint::Min(0)])
+            .split(chart_area);
+        let title = if app.date_range.is_some() { " Token Mix (Selected Range) " } else { " Token Mix (All Time) " };
+        draw_token_mix(f, &app.stats.total_tokens, title, chunks[0]);
+        chunks[1]
+    } else {
+        chart_area
+    };
+    
+    // Costliest projects along the bottom, when asked for and there's room
+    let top_projects = if app.top_projects { top_projects(app) } else { Vec::new() };
+    let top_height = top_projects.len() as u16 + 2;
+    let chart_area = if !top_projects.is_empty() && chart_area.height >= top_height + 3 {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(top_height)])
+            .split(chart_area);
+        draw_top_projects(f, app, &top_projects, chunks[1]);
+        chunks[0]
+    } else {
+        chart_area
+    };
+    
+    // Daily trend beside the last 24 hours, so the current burn is visible at a glance
+    let chart_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(60),
+            Constraint::Percentage(40),
+        ])
+        .split(chart_area);
+    
+    let (title, metric): (_, fn(&DailyUsage) -> u64) = match app.unit {
+        Unit::Cost => (" Daily Cost (Last 30 Days) ", |d| (d.total_cost * 100.0) as u64),
+        Unit::Tokens => (" Daily Tokens (Last 30 Days) ", |d| token_total(&d.tokens)),
+    };
+    let daily_values: Vec<u64> = app.stats.daily.iter()
         .rev()
         .take(30)
-        .map(|d| (d.total_cost * 100.0) as u64)
+        .map(metric)
         .rev()
         .collect();
     
-    if !daily_costs.is_empty() {
+    if !daily_values.is_empty() {
         let sparkline = Sparkline::default()
-            .block(Block::default().borders(Borders::ALL).title(" Daily Usage (Last 30 Days) "))
-            .data(&daily_costs)
+            .block(bordered().title(title))
+            .bar_set(bar_set())
+            .data(&daily_values)
             .style(Style::default().fg(Color::Cyan));
-        f.render_widget(sparkline, chunks[1]);
+        f.render_widget(sparkline, chart_chunks[0]);
     }
+    
+    let (hourly_title, hourly_metric): (_, fn(&HourlyUsage) -> u64) = match app.unit {
+        Unit::Cost => (" Cost per Hour (Last 24h) ", |h| (h.total_cost * 100.0) as u64),
+        Unit::Tokens => (" Tokens per Hour (Last 24h) ", |h| token_total(&h.tokens)),
+    };
+    let hourly_values = last_24_hours(&app.stats.hourly, hourly_metric);
+    let sparkline = Sparkline::default()
+        .block(bordered().title(hourly_title))
+        .bar_set(bar_set())
+        .data(&hourly_values)
+        .style(Style::default().fg(Color::Yellow));
+    f.render_widget(sparkline, chart_chunks[1]);
 }
 
-fn draw_daily(f: &mut Frame, app: &App, area: Rect) {
-    let items: Vec<ListItem> = app.stats.daily.iter()
+/// Up to `TOP_PROJECTS` projects by cost, labeled with their directory names
+fn top_projects(app: &App) -> Vec<(String, f64)> {
+    let projects = app.stats.cost_by_project();
+    let shown = &projects[..projects.len().min(TOP_PROJECTS)];
+    let paths: Vec<&str> = shown.iter().map(|(path, _)| *path).collect();
+    project_basenames(&paths).into_iter()
+        .zip(shown)
+        .map(|(name, (_, cost))| (truncate_path(&name, TOP_PROJECT_LABEL_WIDTH), *cost))
+        .collect()
+}
+
+/// One horizontal bar per project, scaled to the costliest
+fn draw_top_projects(f: &mut Frame, app: &App, projects: &[(String, f64)], area: Rect) {
+    let bars: Vec<Bar> = projects.iter()
+        .map(|(name, cost)| {
+            Bar::default()
+                .label(Line::from(name.as_str()))
+                .value((cost * 100.0).round() as u64)
+                .text_value(app.currency.format(*cost, 2))
+                .style(Style::default().fg(Color::Magenta))
+                .value_style(Style::default().fg(Color::Black).bg(Color::Magenta))
+        })
+        .collect();
+    
+    let title = if app.date_range.is_some() { " Top Projects (Whole Sessions in Range) " } else { " Top Projects (All Time) " };
+    let chart = BarChart::default()
+        .block(bordered().title(title))
+        .bar_set(bar_set())
+        .direction(Direction::Horizontal)
+        .bar_width(1)
+        .bar_gap(0)
+        .data(BarGroup::default().bars(&bars));
+    f.render_widget(chart, area);
+}
+
+/// Single-row stacked bar of tokens by category, with a legend below
+fn draw_token_mix(f: &mut Frame, tokens: &TokenUsage, title: &str, area: Rect) {
+    let segments = [
+        ("input", tokens.input_tokens, Color::Cyan),
+        ("output", tokens.output_tokens, Color::Green),
+        ("cache read", tokens.cache_read_input_tokens, Color::Yellow),
+        ("cache write", tokens.cache_creation_input_tokens, Color::Magenta),
+    ];
+    let total = tokens.total();
+    let width = area.width.saturating_sub(2) as f64;
+    
+    // Round cumulative boundaries so the segments always fill the row exactly
+    let mut bar = Vec::new();
+    let mut legend = Vec::new();
+    let (mut cumulative, mut drawn) = (0u64, 0usize);
+    for (label, count, color) in segments {
+        let share = if total > 0 { count as f64 / total as f64 } else { 0.0 };
+        cumulative = cumulative.saturating_add(count);
+        let end = if total > 0 { (cumulative as f64 / total as f64 * width).round() as usize } else { 0 };
+        bar.push(Span::styled(glyph("█", "#").repeat(end.saturating_sub(drawn)), Style::default().fg(color)));
+        drawn = drawn.max(end);
+        
+        legend.push(Span::styled(glyph("■ ", "# "), Style::default().fg(color)));
+        legend.push(Span::raw(format!("{} {:.1}%   ", label, share * 100.0)));
+    }
+    
+    let widget = Paragraph::new(vec![Line::from(bar), Line::from(legend)])
+        .block(bordered().title(title))
+        .alignment(Alignment::Center);
+    f.render_widget(widget, area);
+}
+
+/// One value per hour for the 24 hours ending with the current one, oldest
+/// first, with idle hours filled in as zero
+fn last_24_hours(hourly: &[HourlyUsage], metric: fn(&HourlyUsage) -> u64) -> Vec<u64> {
+    let current_hour = Utc::now().timestamp().div_euclid(3600);
+    (0..24)
         .rev()
+        .map(|ago| {
+            let hour = current_hour - ago;
+            hourly.iter()
+                .find(|h| h.hour.timestamp().div_euclid(3600) == hour)
+                .map(metric)
+                .unwrap_or(0)
+        })
+        .collect()
+}
+
+fn draw_daily(f: &mut Frame, app: &App, area: Rect) {
+    let items: Vec<ListItem> = app.daily_rows().into_iter()
         .enumerate()
         .map(|(i, d)| {
+            let anomaly = app.anomalies.contains(&d.date);
             let style = if i == app.selected_index {
                 Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+            } else if anomaly {
+                Style::default().fg(Color::Red)
             } else {
                 Style::default()
             };
             
+            let tokens = if app.detailed_tokens {
+                format!(
+                    "{:>10} in  {:>9} out  {:>10} cache write  {:>11} cache read",
+                    format_number(d.tokens.input_tokens),
+                    format_number(d.tokens.output_tokens),
+                    format_number(d.tokens.cache_creation_input_tokens),
+                    format_number(d.tokens.cache_read_input_tokens),
+                )
+            } else {
+                format!("{:>10} tokens", format_number(token_total(&d.tokens)))
+            };
+            
             ListItem::new(Line::from(vec![
+                Span::styled(if anomaly { glyph("⚠ ", "! ") } else { "  " }, style),
                 Span::styled(format!("{:<12}", d.date.format("%Y-%m-%d")), style),
                 Span::raw("  "),
-                Span::styled(format!("{:>10} tokens", format_number(d.tokens.total())), style),
+                Span::styled(tokens, style),
+                Span::raw("  "),
+                Span::styled(format!("{:>9}", app.currency.format(d.total_cost, 2)), cost_style(app, d.total_cost, i, style)),
+                Span::styled(format!(" {:>5.1}% of total", pct_of_total(d.total_cost, app.stats.total_cost)), style),
                 Span::raw("  "),
-                Span::styled(format!("${:>8.2}", d.total_cost), style),
+                Span::styled(format!("{:>5.1}% cache", d.tokens.cache_hit_rate() * 100.0), style),
             ]))
         })
         .collect();
     
+    let title = if app.anomalies_only {
+        " Daily Usage (anomalies only, a to show all) "
+    } else {
+        " Daily Usage "
+    };
     let list = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title(" Daily Usage "));
+        .block(bordered().title(title));
     
     f.render_widget(list, area);
 }
 
 fn draw_sessions(f: &mut Frame, app: &App, area: Rect) {
-    let items: Vec<ListItem> = app.stats.sessions.iter()
-        .take(20)
+    let now = Utc::now();
+    let rows = app.session_rows();
+    let paths: Vec<&str> = rows.iter().map(|s| s.project_path.as_str()).collect();
+    let basenames = project_basenames(&paths);
+    
+    let items: Vec<ListItem> = rows.into_iter()
         .enumerate()
         .map(|(i, s)| {
             let style = if i == app.selected_index {
@@ -258,32 +711,59 @@ fn draw_sessions(f: &mut Frame, app: &App, area: Rect) {
                 Style::default()
             };
             
-            let project = if s.project_path.len() > 40 {
-                format!("{}...", &s.project_path[..37])
+            let show_full = app.show_full_path && i == app.selected_index;
+            let project = if show_full {
+                String::new()
+            } else if app.basename_only {
+                truncate_path(&basenames[i], 40)
             } else {
-                s.project_path.clone()
+                truncate_path(app.display_project(&s.project_path), 40)
             };
             
-            ListItem::new(Line::from(vec![
-                Span::styled(format!("{:<20}", s.last_activity.format("%Y-%m-%d %H:%M")), style),
+            let activity = if app.relative_time {
+                format_relative(s.last_activity, now)
+            } else {
+                s.first_activity.format("%Y-%m-%d %H:%M").to_string()
+            };
+            
+            let mut lines = vec![Line::from(vec![
+                Span::styled(format!("{:<18}", activity), style),
+                Span::styled(format!("{:>8}", format_duration(s.duration())), style),
+                Span::raw("  "),
+                Span::styled(format!("{:>10} tokens", format_number(token_total(&s.tokens))), style),
                 Span::raw("  "),
-                Span::styled(format!("{:>10} tokens", format_number(s.tokens.total())), style),
+                Span::styled(format!("{:>9}", app.currency.format(s.total_cost, 2)), style),
                 Span::raw("  "),
-                Span::styled(format!("${:>8.2}", s.total_cost), style),
+                Span::styled(format!("{:>5.1}% cache", s.tokens.cache_hit_rate() * 100.0), style),
                 Span::raw("  "),
                 Span::styled(project, style),
-            ]))
+            ])];
+            
+            // Give the full path its own line so long paths aren't clipped
+            if show_full {
+                lines.push(Line::from(Span::styled(format!("  {}", s.project_path), style)));
+            }
+            
+            ListItem::new(lines)
         })
         .collect();
     
+    // Sessions aren't split by day, so a range lists those active in it with
+    // their whole-session totals
+    let title = match (app.relative_time, app.date_range.is_some()) {
+        (true, false) => " Recent Sessions (last active, duration) ",
+        (false, false) => " Recent Sessions (started, duration) ",
+        (true, true) => " Sessions in Range (last active, duration, whole-session totals) ",
+        (false, true) => " Sessions in Range (started, duration, whole-session totals) ",
+    };
     let list = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title(" Recent Sessions "));
+        .block(bordered().title(title));
     
     f.render_widget(list, area);
 }
 
 fn draw_monthly(f: &mut Frame, app: &App, area: Rect) {
-    let items: Vec<ListItem> = app.stats.monthly.iter()
+    let items: Vec<ListItem> = app.monthly_rows().into_iter()
         .enumerate()
         .map(|(i, m)| {
             let style = if i == app.selected_index {
@@ -292,32 +772,227 @@ fn draw_monthly(f: &mut Frame, app: &App, area: Rect) {
                 Style::default()
             };
             
-            ListItem::new(Line::from(vec![
+            // Months are judged by their average active day, so the daily thresholds apply
+            let per_day = m.total_cost / m.daily_breakdown.len().max(1) as f64;
+            let mut spans = vec![
                 Span::styled(format!("{:<10}", m.month), style),
                 Span::raw("  "),
-                Span::styled(format!("{:>12} tokens", format_number(m.tokens.total())), style),
+                Span::styled(format!("{:>12} tokens", format_number(token_total(&m.tokens))), style),
+                Span::raw("  "),
+                Span::styled(format!("{:>11}", app.currency.format(m.total_cost, 2)), cost_style(app, per_day, i, style)),
+                Span::styled(format!(" {:>5.1}% of total", pct_of_total(m.total_cost, app.stats.total_cost)), style),
+                Span::raw("  "),
+                Span::styled(format!("{:<9}", format!("{} models", m.models_used.len())), style),
+            ];
+            if app.monthly_spark {
+                spans.push(Span::raw("  "));
+                spans.push(Span::styled(sparkline(&daily_costs(m)), Style::default().fg(Color::Green)));
+            }
+            
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    
+    let list = List::new(items)
+        .block(bordered().title(" Monthly Usage "));
+    
+    f.render_widget(list, area);
+}
+
+/// Row style for a daily cost: green, yellow above `--cost-warn` and red above
+/// `--cost-high`. The selected row keeps its highlight instead.
+fn cost_style(app: &App, daily_cost: f64, index: usize, row_style: Style) -> Style {
+    if index == app.selected_index {
+        row_style
+    } else if daily_cost > app.cost_high {
+        Style::default().fg(Color::Red)
+    } else if daily_cost > app.cost_warn {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default().fg(Color::Green)
+    }
+}
+
+/// A period's share of all-time cost, in percent; zero when nothing has been spent
+fn pct_of_total(cost: f64, total_cost: f64) -> f64 {
+    if total_cost > 0.0 { cost / total_cost * 100.0 } else { 0.0 }
+}
+
+/// Cost for each day of the month up to the last active one, zero on idle days
+fn daily_costs(month: &MonthlyUsage) -> Vec<f64> {
+    let days = month.daily_breakdown.iter().map(|d| d.date.day() as usize).max().unwrap_or(0);
+    let mut costs = vec![0.0; days];
+    for day in &month.daily_breakdown {
+        costs[day.date.day() as usize - 1] += day.total_cost;
+    }
+    costs
+}
+
+fn draw_models(f: &mut Frame, app: &App, area: Rect) {
+    const BAR_WIDTH: usize = 20;
+    let total_cost = app.stats.total_cost;
+    
+    let items: Vec<ListItem> = app.model_rows().into_iter()
+        .enumerate()
+        .map(|(i, m)| {
+            let style = if i == app.selected_index {
+                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            
+            let share = if total_cost > 0.0 { m.total_cost / total_cost } else { 0.0 };
+            let filled = (share * BAR_WIDTH as f64).round() as usize;
+            let bar = format!("{}{}", glyph("█", "#").repeat(filled), glyph("░", ".").repeat(BAR_WIDTH - filled.min(BAR_WIDTH)));
+            
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:<32}", m.model), style),
+                Span::raw("  "),
+                Span::styled(format!("{:>14} tokens", format_number(token_total(&m.tokens))), style),
                 Span::raw("  "),
-                Span::styled(format!("${:>10.2}", m.total_cost), style),
+                Span::styled(format!("{:>11}", app.currency.format(m.total_cost, 2)), style),
                 Span::raw("  "),
-                Span::styled(format!("{} models", m.models_used.len()), style),
+                Span::styled(bar, Style::default().fg(Color::Magenta)),
+                Span::styled(format!(" {:>5.1}%", share * 100.0), style),
             ]))
         })
         .collect();
     
     let list = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title(" Monthly Usage "));
+        .block(bordered().title(" Usage by Model "));
     
     f.render_widget(list, area);
 }
 
-fn format_number(n: u64) -> String {
-    let s = n.to_string();
-    let mut result = String::new();
-    for (i, c) in s.chars().rev().enumerate() {
-        if i > 0 && i % 3 == 0 {
-            result.push(',');
-        }
-        result.push(c);
+/// Plain-ASCII borders for `--ascii`
+const ASCII_BORDER: symbols::border::Set = symbols::border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+/// Bar levels for sparklines and bar charts under `--ascii`
+const ASCII_BARS: symbols::bar::Set = symbols::bar::Set {
+    full: "#",
+    seven_eighths: "#",
+    three_quarters: "=",
+    five_eighths: "=",
+    half: "-",
+    three_eighths: "-",
+    one_quarter: "_",
+    one_eighth: "_",
+    empty: " ",
+};
+
+/// Box around every panel, in ASCII under `--ascii`
+fn bordered() -> Block<'static> {
+    let block = Block::default().borders(Borders::ALL);
+    if ascii() { block.border_set(ASCII_BORDER) } else { block }
+}
+
+fn bar_set() -> symbols::bar::Set {
+    if ascii() { ASCII_BARS } else { symbols::bar::NINE_LEVELS }
+}
+
+/// Shorten a path to at most `max_width` terminal columns. Works on whole
+/// characters and their display width, so multibyte and wide (CJK) paths
+/// never split a codepoint or overflow the column.
+fn truncate_path(path: &str, max_width: usize) -> String {
+    if path.width() <= max_width {
+        return path.to_string();
     }
-    result.chars().rev().collect()
-}
\ No newline at end of file
+    
+    let budget = max_width.saturating_sub(3);
+    let mut width = 0;
+    let kept: String = path.chars()
+        .take_while(|c| {
+            width += c.width().unwrap_or(0);
+            width <= budget
+        })
+        .collect();
+    format!("{}...", kept)
+}
+
+/// Final path component of each project, e.g. the repo folder, prefixed with
+/// its parent directory when different projects would show the same name
+fn project_basenames(paths: &[&str]) -> Vec<String> {
+    let split = |path: &str| -> (String, String) {
+        let mut parts = path.trim_end_matches(['/', '\\']).rsplit(['/', '\\']);
+        let name = parts.next().unwrap_or_default().to_string();
+        let parent = parts.next().unwrap_or_default().to_string();
+        (parent, name)
+    };
+    
+    paths.iter()
+        .map(|path| {
+            let (parent, name) = split(path);
+            if name.is_empty() {
+                return path.to_string();
+            }
+            let clashes = paths.iter().any(|other| other != path && split(other).1 == name);
+            if clashes && !parent.is_empty() {
+                format!("{}/{}", parent, name)
+            } else {
+                name
+            }
+        })
+        .collect()
+}
+
+fn format_duration(duration: chrono::Duration) -> String {
+    let minutes = duration.num_minutes();
+    if minutes >= 60 {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// Humanized age such as "5m ago", falling back to the absolute time after a week
+fn format_relative(time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let elapsed = now - time;
+    if elapsed.num_days() >= 7 {
+        time.format("%Y-%m-%d %H:%M").to_string()
+    } else if elapsed.num_days() >= 1 {
+        format!("{}d ago", elapsed.num_days())
+    } else if elapsed.num_hours() >= 1 {
+        format!("{}h ago", elapsed.num_hours())
+    } else if elapsed.num_minutes() >= 1 {
+        format!("{}m ago", elapsed.num_minutes())
+    } else {
+        "just now".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_path_keeps_paths_that_fit() {
+        assert_eq!(truncate_path("/home/me/project", 16), "/home/me/project");
+        assert_eq!(truncate_path("/home/me/project", 12), "/home/me/...");
+    }
+
+    #[test]
+    fn truncate_path_cuts_on_character_boundaries() {
+        // 'é' is two bytes, so a byte-indexed cut at 5 would split it
+        assert_eq!(truncate_path("abcdéfghij", 8), "abcdé...");
+        assert_eq!(truncate_path("abcdéfghij", 7), "abcd...");
+    }
+
+    #[test]
+    fn truncate_path_counts_wide_characters_as_two_columns() {
+        let truncated = truncate_path("/项目/代码库/前端", 10);
+        assert_eq!(truncated, "/项目/...");
+        assert!(truncated.width() <= 10);
+
+        // A wide character that would straddle the budget is dropped whole
+        assert_eq!(truncate_path("项目代码库", 8), "项目...");
+    }
+}
@@ -0,0 +1,88 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use crate::models::TokenUsage;
+use crate::tui::app::{App, Tab};
+use crate::util::{csv_field, token_total};
+
+const TOKEN_HEADERS: &str = "input_tokens,output_tokens,cache_creation_tokens,cache_read_tokens,total_tokens,cost_usd";
+
+/// Write the list shown in the current tab to a timestamped CSV in the working directory
+pub fn export_current_view(app: &App) -> Result<PathBuf> {
+    let (name, header, rows): (_, _, Vec<String>) = match app.selected_tab {
+        Tab::Overview => anyhow::bail!("Nothing to export on the Overview tab"),
+        Tab::Daily => (
+            "daily",
+            format!("date,{}", TOKEN_HEADERS),
+            app.daily_rows()
+                .into_iter()
+                .map(|d| format!("{},{}", d.date, token_fields(&d.tokens, d.total_cost)))
+                .collect(),
+        ),
+        Tab::Sessions => (
+            "sessions",
+            format!("session_id,project_path,first_activity,last_activity,{}", TOKEN_HEADERS),
+            app.session_rows()
+                .into_iter()
+                .map(|s| {
+                    format!(
+                        "{},{},{},{},{}",
+                        csv_field(&s.session_id),
+                        csv_field(&s.project_path),
+                        s.first_activity.to_rfc3339(),
+                        s.last_activity.to_rfc3339(),
+                        token_fields(&s.tokens, s.total_cost)
+                    )
+                })
+                .collect(),
+        ),
+        Tab::Monthly => (
+            "monthly",
+            format!("month,{}", TOKEN_HEADERS),
+            app.monthly_rows()
+                .into_iter()
+                .map(|m| format!("{},{}", m.month, token_fields(&m.tokens, m.total_cost)))
+                .collect(),
+        ),
+        Tab::Models => (
+            "models",
+            format!("model,{}", TOKEN_HEADERS),
+            app.model_rows()
+                .into_iter()
+                .map(|m| format!("{},{}", csv_field(&m.model), token_fields(&m.tokens, m.total_cost)))
+                .collect(),
+        ),
+    };
+
+    let path = PathBuf::from(format!(
+        "cc-monitor-{}-{}.csv",
+        name,
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    let file = File::create(&path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+
+    writeln!(writer, "{}", header)?;
+    for row in rows {
+        writeln!(writer, "{}", row)?;
+    }
+    writer.flush()?;
+
+    Ok(path)
+}
+
+fn token_fields(tokens: &TokenUsage, cost: f64) -> String {
+    format!(
+        "{},{},{},{},{},{:.6}",
+        tokens.input_tokens,
+        tokens.output_tokens,
+        tokens.cache_creation_input_tokens,
+        tokens.cache_read_input_tokens,
+        token_total(tokens),
+        cost
+    )
+}
@@ -1,5 +1,7 @@
 pub mod dashboard;
 pub mod app;
+pub mod export;
+pub mod state;
 
 pub use dashboard::*;
 pub use app::*;
\ No newline at end of file
@@ -0,0 +1,37 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::tui::app::Tab;
+
+/// Dashboard view remembered between launches
+#[derive(Serialize, Deserialize)]
+struct DashboardState {
+    selected_tab: Tab,
+}
+
+fn state_path() -> Option<PathBuf> {
+    directories::ProjectDirs::from("", "", "cc-monitor")
+        .map(|dirs| dirs.cache_dir().join("dashboard-state.json"))
+}
+
+/// Tab open when the dashboard was last closed, if any was recorded
+pub fn load_selected_tab() -> Option<Tab> {
+    let contents = fs::read_to_string(state_path()?).ok()?;
+    serde_json::from_str::<DashboardState>(&contents)
+        .ok()
+        .map(|state| state.selected_tab)
+}
+
+pub fn save_selected_tab(selected_tab: Tab) -> Result<()> {
+    let path = state_path().context("No cache directory available")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let contents = serde_json::to_string(&DashboardState { selected_tab })?;
+    fs::write(&path, contents)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
@@ -0,0 +1,371 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::hash_map::RandomState;
+use std::fs::{self, File};
+use std::hash::BuildHasher;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::cli::FooterStyle;
+use crate::models::{TokenUsage, UsageStats};
+
+/// Digit-grouping character, set once at startup from `--thousands-sep` or the locale
+static THOUSANDS_SEPARATOR: OnceLock<char> = OnceLock::new();
+
+/// Whether `--json` output is printed on one line, set once from `--compact`
+static COMPACT_JSON: OnceLock<bool> = OnceLock::new();
+
+/// Decimal places JSON costs are rounded to, set once from `--round`
+static COST_DECIMALS: OnceLock<u32> = OnceLock::new();
+
+/// Report destination, set once from `--output`; stdout when unset
+static OUTPUT_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// `OUTPUT_PATH`, created on the first write so a command that fails or never
+/// prints a report doesn't leave an empty file behind
+static OUTPUT_FILE: OnceLock<Result<File, String>> = OnceLock::new();
+
+/// Secret key for `pseudonym`, drawn on first use
+static PSEUDONYM_KEY: OnceLock<RandomState> = OnceLock::new();
+
+/// Leave cache-read tokens out of reported totals, set once from `--exclude-cache-read`
+static EXCLUDE_CACHE_READ: OnceLock<bool> = OnceLock::new();
+
+/// Draw with ASCII instead of block, arrow and dash glyphs, set once from
+/// `--ascii` or `CC_MONITOR_ASCII`
+static ASCII: OnceLock<bool> = OnceLock::new();
+
+/// What text reports print underneath, set once from `--footer-style`
+static FOOTER_STYLE: OnceLock<FooterStyle> = OnceLock::new();
+
+pub fn set_thousands_separator(separator: char) {
+    let _ = THOUSANDS_SEPARATOR.set(separator);
+}
+
+pub fn set_compact_json(compact: bool) {
+    let _ = COMPACT_JSON.set(compact);
+}
+
+/// Send report output to `path` instead of stdout. The file and its parent
+/// directories are created when the first report line is written.
+pub fn set_output_file(path: &Path) {
+    let _ = OUTPUT_PATH.set(path.to_path_buf());
+}
+
+/// Writer for report output: the `--output` file when given, otherwise stdout
+pub fn output() -> Box<dyn Write> {
+    match OUTPUT_PATH.get() {
+        Some(_) => Box::new(OutputFile),
+        None => Box::new(io::stdout()),
+    }
+}
+
+/// Whether report output goes to a terminal rather than a file or pipe
+pub fn output_is_terminal() -> bool {
+    OUTPUT_PATH.get().is_none() && io::stdout().is_terminal()
+}
+
+/// Handle on the `--output` file that opens it on first use
+struct OutputFile;
+
+impl OutputFile {
+    fn file() -> io::Result<&'static File> {
+        let path = OUTPUT_PATH.get().ok_or_else(|| io::Error::other("no --output file set"))?;
+        OUTPUT_FILE
+            .get_or_init(|| create_output_file(path).map_err(|e| format!("{:#}", e)))
+            .as_ref()
+            .map_err(|e| io::Error::other(e.clone()))
+    }
+}
+
+impl Write for OutputFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Self::file()?.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Self::file()?.flush()
+    }
+}
+
+fn create_output_file(path: &Path) -> Result<File> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    File::create(path).with_context(|| format!("Failed to create {}", path.display()))
+}
+
+/// `println!` to the report destination, returning the write's `io::Result`
+macro_rules! outln {
+    ($($arg:tt)*) => {{
+        use std::io::Write as _;
+        writeln!($crate::util::output(), $($arg)*)
+    }};
+}
+pub(crate) use outln;
+
+pub fn set_cost_decimals(places: u32) {
+    let _ = COST_DECIMALS.set(places);
+}
+
+pub fn set_exclude_cache_read(exclude: bool) {
+    let _ = EXCLUDE_CACHE_READ.set(exclude);
+}
+
+pub fn set_ascii(ascii: bool) {
+    let _ = ASCII.set(ascii);
+}
+
+pub fn ascii() -> bool {
+    ASCII.get().copied().unwrap_or(false)
+}
+
+/// `fancy`, or its ASCII stand-in `plain` under `--ascii`
+pub fn glyph(fancy: &'static str, plain: &'static str) -> &'static str {
+    if ascii() { plain } else { fancy }
+}
+
+pub fn set_footer_style(style: FooterStyle) {
+    let _ = FOOTER_STYLE.set(style);
+}
+
+pub fn excludes_cache_read() -> bool {
+    EXCLUDE_CACHE_READ.get().copied().unwrap_or(false)
+}
+
+/// Which token-total definition is in effect, for JSON consumers
+pub fn token_definition() -> &'static str {
+    if excludes_cache_read() { "excludes_cache_read" } else { "includes_cache_read" }
+}
+
+/// Note printed under text reports when `--exclude-cache-read` changes what "tokens" means
+pub fn print_token_definition_note() -> io::Result<()> {
+    if excludes_cache_read() {
+        outln!("\nToken totals exclude cache reads (--exclude-cache-read).")?;
+    }
+    Ok(())
+}
+
+/// Footer under text reports saying when they were generated and what dates
+/// the data covers, so saved output describes itself
+pub fn print_report_footer(stats: &UsageStats) -> io::Result<()> {
+    let now = chrono::Local::now();
+    match FOOTER_STYLE.get().copied().unwrap_or_default() {
+        FooterStyle::None => {}
+        FooterStyle::Compact => {
+            let range = stats.date_range()
+                .map_or_else(|| "no data".to_string(), |(first, last, _)| format!("data {} to {}", first, last));
+            outln!("\nGenerated {}, {}", now.format("%Y-%m-%d %H:%M"), range)?;
+        }
+        FooterStyle::Full => {
+            outln!(
+                "\nGenerated {} by cc-monitor {}",
+                now.format("%Y-%m-%d %H:%M:%S %:z"),
+                env!("CARGO_PKG_VERSION")
+            )?;
+            if let Some((first, last, days)) = stats.date_range() {
+                let plural = |n: usize, word: &str| format!("{} {}{}", n, word, if n == 1 { "" } else { "s" });
+                outln!(
+                    "Data from {} to {} ({}), {}",
+                    first,
+                    last,
+                    plural(days as usize, "day"),
+                    plural(stats.sessions.len(), "session")
+                )?;
+            } else {
+                outln!("No usage data")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Headline token count for reports: every token, or all but cache reads
+/// with `--exclude-cache-read`
+pub fn token_total(tokens: &TokenUsage) -> u64 {
+    if excludes_cache_read() {
+        tokens.total_billable()
+    } else {
+        tokens.total()
+    }
+}
+
+/// Quote a field if it contains CSV metacharacters
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Stand-in for a path or id, e.g. `project-1f0c9e3a`, for `--anonymize`.
+/// Keyed with a secret drawn once per run, so the same input gets the same
+/// name throughout one report but names can't be reversed by hashing guessed
+/// paths or matched up between runs.
+pub fn pseudonym(prefix: &str, value: &str) -> String {
+    // std's RandomState seeds SipHash from the OS random source
+    let key = PSEUDONYM_KEY.get_or_init(RandomState::new);
+    format!("{}-{:08x}", prefix, key.hash_one(value) as u32)
+}
+
+/// Blended cost per million tokens, counted as in `token_total`; `None` when
+/// no tokens were used
+pub fn effective_rate_per_mtok(cost: f64, tokens: &TokenUsage) -> Option<f64> {
+    let tokens = token_total(tokens);
+    (tokens > 0).then(|| cost / tokens as f64 * 1_000_000.0)
+}
+
+/// Serialize `--json` output, pretty-printed unless `--compact` was given
+pub fn to_json_string<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<String> {
+    to_json(value, !COMPACT_JSON.get().copied().unwrap_or(false))
+}
+
+/// Serialize JSON output, rounding costs when `--round` was given
+pub fn to_json<T: Serialize + ?Sized>(value: &T, pretty: bool) -> serde_json::Result<String> {
+    let Some(&places) = COST_DECIMALS.get() else {
+        return if pretty { serde_json::to_string_pretty(value) } else { serde_json::to_string(value) };
+    };
+    
+    let mut value = serde_json::to_value(value)?;
+    round_costs(&mut value, places, false);
+    if pretty { serde_json::to_string_pretty(&value) } else { serde_json::to_string(&value) }
+}
+
+/// Convert to a JSON value with the same `--round` handling as `to_json`
+pub fn to_json_value<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<serde_json::Value> {
+    let mut value = serde_json::to_value(value)?;
+    if let Some(&places) = COST_DECIMALS.get() {
+        round_costs(&mut value, places, false);
+    }
+    Ok(value)
+}
+
+/// Round every number under a key mentioning "cost" (`total_cost`,
+/// `cost_per_hour`, the whole `cost_breakdown`, ...) to `places` decimals
+fn round_costs(value: &mut serde_json::Value, places: u32, in_cost: bool) {
+    match value {
+        serde_json::Value::Number(n) if in_cost => {
+            if let Some(rounded) = n.as_f64()
+                .filter(|_| !n.is_u64() && !n.is_i64())
+                .map(|f| round_to(f, places))
+                .and_then(serde_json::Number::from_f64)
+            {
+                *n = rounded;
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                round_costs(item, places, in_cost);
+            }
+        }
+        serde_json::Value::Object(object) => {
+            for (key, item) in object {
+                round_costs(item, places, in_cost || key.contains("cost"));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn round_to(value: f64, places: u32) -> f64 {
+    let factor = 10f64.powi(places as i32);
+    (value * factor).round() / factor
+}
+
+/// Grouping separator for the user's locale (`LC_ALL`, then `LC_NUMERIC`, then `LANG`),
+/// e.g. `de_DE.UTF-8` -> '.', `fr_FR.UTF-8` -> ' ', falling back to ','
+pub fn locale_thousands_separator() -> char {
+    let locale = ["LC_ALL", "LC_NUMERIC", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default();
+    thousands_separator_for(&locale)
+}
+
+/// Grouping separator for a locale name such as `de_DE.UTF-8`, chosen by its language
+fn thousands_separator_for(locale: &str) -> char {
+    let language = locale.split(['_', '.', '-']).next().unwrap_or_default();
+
+    match language {
+        "de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr" | "el" => '.',
+        "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "nb" | "fi" | "uk" | "hu" => ' ',
+        _ => ',',
+    }
+}
+
+/// `n` with digits grouped in threes by the `--thousands-sep` setting
+pub fn format_number(n: u64) -> String {
+    format_number_with(n, *THOUSANDS_SEPARATOR.get().unwrap_or(&','))
+}
+
+/// `n` with digits grouped in threes by `separator`
+pub fn format_number_with(n: u64, separator: char) -> String {
+    let s = n.to_string();
+    let mut result = String::new();
+    for (i, c) in s.chars().rev().enumerate() {
+        if i > 0 && i % 3 == 0 {
+            result.push(separator);
+        }
+        result.push(c);
+    }
+    result.chars().rev().collect()
+}
+
+/// Render values as a row of block characters scaled to the largest value
+pub fn sparkline(values: &[f64]) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    const ASCII_BLOCKS: [char; 8] = ['_', '.', ',', '-', '=', '+', '*', '#'];
+    let blocks = if ascii() { ASCII_BLOCKS } else { BLOCKS };
+    let max = values.iter().copied().fold(0.0, f64::max);
+    values.iter()
+        .map(|&v| {
+            if max <= 0.0 {
+                blocks[0]
+            } else {
+                blocks[((v / max) * (blocks.len() - 1) as f64).round() as usize]
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_number_groups_digits_in_threes() {
+        for separator in [',', '.', ' '] {
+            let group = |s: &str| s.replace(',', &separator.to_string());
+            assert_eq!(format_number_with(0, separator), "0");
+            assert_eq!(format_number_with(999, separator), "999");
+            assert_eq!(format_number_with(1_000, separator), group("1,000"));
+            assert_eq!(format_number_with(1_234_567, separator), group("1,234,567"));
+            assert_eq!(format_number_with(u64::MAX, separator), group("18,446,744,073,709,551,615"));
+        }
+    }
+
+    #[test]
+    fn pseudonyms_are_consistent_within_a_run() {
+        let name = pseudonym("project", "/home/me/project");
+        assert_eq!(name, pseudonym("project", "/home/me/project"));
+        assert_ne!(name, pseudonym("project", "/home/me/project2"));
+        assert!(name.starts_with("project-"));
+        assert_eq!(name.len(), "project-".len() + 8);
+    }
+
+    #[test]
+    fn thousands_separator_follows_the_locale_language() {
+        assert_eq!(thousands_separator_for("de_DE.UTF-8"), '.');
+        assert_eq!(thousands_separator_for("pt-BR"), '.');
+        assert_eq!(thousands_separator_for("fr_FR.UTF-8"), ' ');
+        assert_eq!(thousands_separator_for("sv_SE"), ' ');
+        assert_eq!(thousands_separator_for("en_US.UTF-8"), ',');
+        assert_eq!(thousands_separator_for("C.UTF-8"), ',');
+        assert_eq!(thousands_separator_for("POSIX"), ',');
+        assert_eq!(thousands_separator_for(""), ',');
+    }
+}